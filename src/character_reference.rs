@@ -0,0 +1,228 @@
+//! Decoding of the HTML character references, like `&eacute;`, `&#233;` or `&#xE9;`.
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+use entities::ENTITIES;
+
+/// Decode the character references of a text, like `Effectu&eacute;e`
+pub fn decode_character_references(text: &str) -> Cow<'_, str> {
+    decode(text, false)
+}
+
+/// Decode the character references of an attribute value. Like in a browser, a named reference
+/// without `;` followed by `=` or a letter is not decoded, so `?a=1&copy=2` is kept as is.
+pub fn decode_attribute_value(value: &str) -> Cow<'_, str> {
+    decode(value, true)
+}
+
+fn decode(html: &str, is_attribute_value: bool) -> Cow<'_, str> {
+    let mut ampersand_position = match html.find('&') {
+        Some(position) => position,
+        None => return Cow::Borrowed(html),
+    };
+
+    let mut decoded = String::with_capacity(html.len());
+    let mut copied_length = 0;
+    loop {
+        let reference = &html[ampersand_position + 1..];
+        let replacement = if let Some(reference) = reference.strip_prefix('#') {
+            read_numeric_reference(reference)
+                .map(|(c, length)| (Cow::Owned(c.to_string()), length + 1))
+        } else {
+            read_named_reference(reference, is_attribute_value)
+                .map(|(characters, length)| (Cow::Borrowed(characters), length))
+        };
+
+        let mut next_position = ampersand_position + 1;
+        if let Some((characters, length)) = replacement {
+            decoded.push_str(&html[copied_length..ampersand_position]);
+            decoded.push_str(&characters);
+            next_position += length;
+            copied_length = next_position;
+        }
+
+        match html[next_position..].find('&') {
+            Some(position) => ampersand_position = next_position + position,
+            None => break,
+        }
+    }
+    decoded.push_str(&html[copied_length..]);
+    Cow::Owned(decoded)
+}
+
+/// Read a numeric reference after `&#`, like `233;` or `xE9;`. It returns the character and the
+/// length of the reference.
+fn read_numeric_reference(reference: &str) -> Option<(char, usize)> {
+    let (radix, prefix_length) = match reference.as_bytes().first() {
+        Some(b'x') | Some(b'X') => (16, 1),
+        _ => (10, 0),
+    };
+    let digits = &reference[prefix_length..];
+    let digits_length = digits
+        .bytes()
+        .take_while(|c| (*c as char).is_digit(radix))
+        .count();
+    if digits_length == 0 {
+        return None;
+    }
+
+    let code = digits[..digits_length]
+        .chars()
+        .filter_map(|c| c.to_digit(radix))
+        .fold(0u32, |code, digit| {
+            code.saturating_mul(radix).saturating_add(digit)
+        });
+    let has_semicolon = digits[digits_length..].starts_with(';');
+
+    let c = windows_1252_c1_char(code)
+        .or_else(|| char::from_u32(code).filter(|c| *c != '\0'))
+        .unwrap_or(char::REPLACEMENT_CHARACTER);
+    Some((c, prefix_length + digits_length + has_semicolon as usize))
+}
+
+/// Read a named reference after `&`, like `eacute;`. It returns the characters and the length of
+/// the reference.
+fn read_named_reference(
+    reference: &str,
+    is_attribute_value: bool,
+) -> Option<(&'static str, usize)> {
+    let name_length = reference
+        .bytes()
+        .take_while(u8::is_ascii_alphanumeric)
+        .count();
+    if name_length == 0 {
+        return None;
+    }
+
+    let named_references = named_references();
+    if reference[name_length..].starts_with(';') {
+        if let Some(characters) = named_references.get(&reference[..=name_length]) {
+            return Some((characters, name_length + 1));
+        }
+    }
+
+    // some references are also known without `;`, like `&amp` : the longest one is read
+    let (characters, length) = (1..=name_length).rev().find_map(|length| {
+        named_references
+            .get(&reference[..length])
+            .map(|c| (*c, length))
+    })?;
+    let next_character = reference[length..].chars().next();
+    let is_kept_in_attribute_value =
+        next_character.is_some_and(|c| c == '=' || c.is_ascii_alphanumeric());
+    if is_attribute_value && is_kept_in_attribute_value {
+        None
+    } else {
+        Some((characters, length))
+    }
+}
+
+/// Named references, like `eacute;`, with their characters
+fn named_references() -> &'static HashMap<&'static str, &'static str> {
+    static NAMED_REFERENCES: OnceLock<HashMap<&'static str, &'static str>> = OnceLock::new();
+    NAMED_REFERENCES.get_or_init(|| {
+        ENTITIES
+            .iter()
+            .map(|entity| (&entity.entity[1..], entity.characters))
+            .collect()
+    })
+}
+
+/// Characters of the codes 0x80 to 0x9F in the Windows-1252 encoding. A numeric reference to
+/// these codes refers to these characters, like in a browser.
+pub(crate) fn windows_1252_c1_char(code: u32) -> Option<char> {
+    let c = match code {
+        0x80 => '\u{20AC}',
+        0x82 => '\u{201A}',
+        0x83 => '\u{0192}',
+        0x84 => '\u{201E}',
+        0x85 => '\u{2026}',
+        0x86 => '\u{2020}',
+        0x87 => '\u{2021}',
+        0x88 => '\u{02C6}',
+        0x89 => '\u{2030}',
+        0x8A => '\u{0160}',
+        0x8B => '\u{2039}',
+        0x8C => '\u{0152}',
+        0x8E => '\u{017D}',
+        0x91 => '\u{2018}',
+        0x92 => '\u{2019}',
+        0x93 => '\u{201C}',
+        0x94 => '\u{201D}',
+        0x95 => '\u{2022}',
+        0x96 => '\u{2013}',
+        0x97 => '\u{2014}',
+        0x98 => '\u{02DC}',
+        0x99 => '\u{2122}',
+        0x9A => '\u{0161}',
+        0x9B => '\u{203A}',
+        0x9C => '\u{0153}',
+        0x9E => '\u{017E}',
+        0x9F => '\u{0178}',
+        _ => return None,
+    };
+    Some(c)
+}
+
+#[cfg(test)]
+mod test_character_reference {
+
+    use super::*;
+
+    #[test]
+    fn should_not_copy_a_text_without_reference() {
+        assert!(matches!(
+            decode_character_references("hello"),
+            Cow::Borrowed("hello")
+        ));
+    }
+
+    #[test]
+    fn should_decode_named_references() {
+        assert_eq!(
+            "Effectuée le 8 juin 2019",
+            decode_character_references("Effectu&eacute;e le 8 juin 2019")
+        );
+        assert_eq!(
+            "2\u{a0}x Siège \u{2233} \u{2242}\u{338}",
+            decode_character_references(
+                "2&nbsp;x Si&egrave;ge &CounterClockwiseContourIntegral; &NotEqualTilde;"
+            )
+        );
+    }
+
+    #[test]
+    fn should_decode_numeric_references() {
+        assert_eq!("é é é", decode_character_references("&#233; &#xE9; &#XE9"));
+        assert_eq!(
+            "€ \u{FFFD} \u{FFFD}",
+            decode_character_references("&#128; &#0; &#xD800;")
+        );
+        assert_eq!("\u{FFFD}", decode_character_references("&#99999999999;"));
+    }
+
+    #[test]
+    fn should_keep_what_is_not_a_reference() {
+        assert_eq!(
+            "& &; &# &#x; &foo;",
+            decode_character_references("& &; &# &#x; &foo;")
+        );
+        assert_eq!("AT&T", decode_character_references("AT&T"));
+    }
+
+    #[test]
+    fn should_decode_the_longest_reference_known_without_semicolon() {
+        assert_eq!("¬it; ∉", decode_character_references("&notit; &notin;"));
+        assert_eq!("a&b", decode_character_references("a&ampb"));
+    }
+
+    #[test]
+    fn should_keep_a_reference_without_semicolon_followed_by_a_letter_in_attribute_value() {
+        assert_eq!("?a=1&b=2", decode_attribute_value("?a=1&amp;b=2"));
+        assert_eq!("?a=1&copy=2", decode_attribute_value("?a=1&copy=2"));
+        assert_eq!("?a=1&ampb", decode_attribute_value("?a=1&ampb"));
+        assert_eq!("© 2019", decode_attribute_value("&copy 2019"));
+    }
+}
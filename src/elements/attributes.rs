@@ -0,0 +1,202 @@
+use std::ops::Range;
+use std::slice::{Iter, IterMut};
+
+use crate::elements::start_element::{fold_name_case, split_qualified_name};
+
+/// quote around an attribute value
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum AttributeQuote {
+    /// like `class="foo"`
+    Double,
+    /// like `class='foo'`
+    Single,
+    /// like `width=100`, or an attribute without value like `hidden`
+    None,
+}
+
+/// An attribute of an HTML tag : its name, its value and their positions into the tag
+#[derive(PartialEq, Debug, Clone)]
+pub struct Attribute {
+    pub name: String,
+    /// name as written in the HTML code, like `VALIGN`, when `name` was lowercased
+    pub original_name: Option<String>,
+    /// `None` when the attribute has no value, like `hidden`. It is not the same as an empty
+    /// value, like `data-x=""`.
+    pub value: Option<String>,
+    /// byte range of the name, from the `<` of the tag
+    pub name_span: Range<usize>,
+    /// byte range of the value without the quotes, from the `<` of the tag. It is `None` when
+    /// the attribute has no value, like `hidden`.
+    pub value_span: Option<Range<usize>>,
+    pub quote: AttributeQuote,
+}
+
+impl Attribute {
+    /// Returns true if the attribute has no value, like `hidden`
+    pub fn is_boolean(&self) -> bool {
+        self.value.is_none()
+    }
+
+    /// Value of the attribute, like in CSS selectors : an attribute without value has an empty
+    /// value.
+    pub fn value_or_empty(&self) -> &str {
+        self.value.as_deref().unwrap_or_default()
+    }
+
+    /// Namespace prefix of the attribute name, like `xlink` for `xlink:href`
+    pub fn prefix(&self) -> Option<&str> {
+        split_qualified_name(&self.name).0
+    }
+
+    /// Attribute name without its namespace prefix, like `href` for `xlink:href`
+    pub fn local_name(&self) -> &str {
+        split_qualified_name(&self.name).1
+    }
+
+    /// Attribute name as written in the HTML code
+    pub fn written_name(&self) -> &str {
+        self.original_name.as_deref().unwrap_or(&self.name)
+    }
+
+    /// Lowercase the ASCII letters of the name, like an HTML parser does. The value keeps its
+    /// case.
+    pub fn fold_case(&mut self) {
+        fold_name_case(&mut self.name, &mut self.original_name);
+    }
+}
+
+/// Attributes of an HTML tag in the order of the HTML code. Repeated attributes are all kept,
+/// but only the first one is found by `get`, like in a browser.
+#[derive(PartialEq, Debug, Default, Clone)]
+pub struct Attributes {
+    attributes: Vec<Attribute>,
+}
+
+impl Attributes {
+    pub fn new() -> Self {
+        Attributes::default()
+    }
+
+    /// Add an attribute after the others
+    pub fn push(&mut self, attribute: Attribute) {
+        self.attributes.push(attribute);
+    }
+
+    /// Remove all the attributes, moving them at the end of `unused` so their memory is reused
+    pub(crate) fn move_into(&mut self, unused: &mut Vec<Attribute>) {
+        unused.append(&mut self.attributes);
+    }
+
+    /// Get the value of the first attribute with the given name. An attribute without value has
+    /// an empty value : use `get_attribute` to know if the attribute has a value.
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.get_attribute(name).map(Attribute::value_or_empty)
+    }
+
+    /// Get the first attribute with the given name
+    pub fn get_attribute(&self, name: &str) -> Option<&Attribute> {
+        self.attributes
+            .iter()
+            .find(|attribute| attribute.name == name)
+    }
+
+    /// Get all the attributes with the given name, in the order of the HTML code
+    pub fn get_all<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Attribute> {
+        self.attributes
+            .iter()
+            .filter(move |attribute| attribute.name == name)
+    }
+
+    pub fn contains_key(&self, name: &str) -> bool {
+        self.get_attribute(name).is_some()
+    }
+
+    /// count of attributes, including the repeated ones
+    pub fn len(&self) -> usize {
+        self.attributes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.attributes.is_empty()
+    }
+
+    pub fn iter(&self) -> Iter<'_, Attribute> {
+        self.attributes.iter()
+    }
+
+    pub fn iter_mut(&mut self) -> IterMut<'_, Attribute> {
+        self.attributes.iter_mut()
+    }
+}
+
+impl<'a> IntoIterator for &'a Attributes {
+    type Item = &'a Attribute;
+    type IntoIter = Iter<'a, Attribute>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.attributes.iter()
+    }
+}
+
+#[cfg(test)]
+mod test_attributes {
+
+    use super::*;
+
+    fn build_attribute(name: &str, value: &str) -> Attribute {
+        Attribute {
+            name: name.to_string(),
+            original_name: None,
+            value: Some(value.to_string()),
+            name_span: 0..0,
+            value_span: None,
+            quote: AttributeQuote::None,
+        }
+    }
+
+    #[test]
+    fn should_keep_attributes_order_and_find_the_first_repeated_attribute() {
+        let mut attributes = Attributes::new();
+        attributes.push(build_attribute("class", "foo"));
+        attributes.push(build_attribute("id", "bar"));
+        attributes.push(build_attribute("class", "baz"));
+
+        assert_eq!(3, attributes.len());
+        assert_eq!(Some("foo"), attributes.get("class"));
+        assert!(attributes.contains_key("id"));
+        assert!(!attributes.contains_key("title"));
+        let names: Vec<&str> = attributes.iter().map(|a| a.name.as_str()).collect();
+        assert_eq!(vec!["class", "id", "class"], names);
+        let classes: Vec<&str> = attributes
+            .get_all("class")
+            .map(|a| a.value_or_empty())
+            .collect();
+        assert_eq!(vec!["foo", "baz"], classes);
+    }
+
+    #[test]
+    fn should_read_an_attribute_without_value_as_an_empty_value() {
+        let mut attributes = Attributes::new();
+        attributes.push(Attribute {
+            value: None,
+            ..build_attribute("hidden", "")
+        });
+        attributes.push(build_attribute("data-x", ""));
+
+        assert_eq!(Some(""), attributes.get("hidden"));
+        assert_eq!(Some(""), attributes.get("data-x"));
+        assert!(attributes.get_attribute("hidden").unwrap().is_boolean());
+        assert!(!attributes.get_attribute("data-x").unwrap().is_boolean());
+    }
+
+    #[test]
+    fn should_split_the_namespace_prefix_of_an_attribute_name() {
+        let attribute = build_attribute("xlink:href", "#a");
+        assert_eq!(Some("xlink"), attribute.prefix());
+        assert_eq!("href", attribute.local_name());
+
+        let attribute = build_attribute("href", "#a");
+        assert_eq!(None, attribute.prefix());
+        assert_eq!("href", attribute.local_name());
+    }
+}
@@ -0,0 +1,62 @@
+use crate::elements::utils::extract_multiline_element_like;
+
+/// Extract the content of a CDATA section like `<![CDATA[a < b]]>`
+fn extract_cdata_content(html: &str) -> Option<(&str, usize)> {
+    extract_multiline_element_like(html, "<![CDATA[", "]]>")
+}
+
+/// return true if the element starts with `<![CDATA[`
+fn is_cdata_element(html: &str) -> bool {
+    // min length 12, like `<![CDATA[]]>`
+    html.len() >= 12 && html.starts_with("<![CDATA[")
+}
+
+use crate::elements::Element;
+
+/// CDATA section of an XML document : its content is a text where `<` and `&` are not read
+#[derive(PartialEq, Debug)]
+pub struct CdataElement<'a> {
+    pub content: &'a str,
+    pub length: usize,
+}
+
+impl<'a> Element<'a, CdataElement<'a>> for CdataElement<'a> {
+    fn extract(html: &'a str) -> Option<CdataElement<'a>> {
+        if is_cdata_element(html) {
+            let (content, length) = extract_cdata_content(html)?;
+            Some(CdataElement { content, length })
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_cdata_element {
+    use super::*;
+
+    #[test]
+    fn should_extract_the_content_of_a_cdata_section() {
+        assert_eq!(
+            Some(CdataElement {
+                content: "a < b && c",
+                length: 22
+            }),
+            CdataElement::extract("<![CDATA[a < b && c]]>foo")
+        );
+        assert_eq!(
+            Some(CdataElement {
+                content: "",
+                length: 12
+            }),
+            CdataElement::extract("<![CDATA[]]>")
+        );
+    }
+
+    #[test]
+    fn should_return_none_given_a_cdata_section_without_end() {
+        assert_eq!(None, CdataElement::extract("<![CDATA[a > b]>"));
+        assert_eq!(None, CdataElement::extract("<![cdata[a]]>"));
+        assert_eq!(None, CdataElement::extract("<!-- a -->"));
+    }
+}
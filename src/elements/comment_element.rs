@@ -1,14 +1,15 @@
 use crate::elements::utils::extract_multiline_element_like;
 
 /// Parse an starting HTML tag like `<div id'foo' class="bar" hidden aria-label='baz'>`
-fn extract_comment_content(html: &str) -> (String, usize) {
+fn extract_comment_content(html: &str) -> Option<(&str, usize)> {
     extract_multiline_element_like(html, "<!--", "-->")
 }
 
 /// return true if the element starts with `</` and a letter
 fn is_comment_element(html: &str) -> bool {
     // min length 4, like `<!-- -->`
-    is_element_like(html, "<!--", 8)
+    // min length 7, like `<!---->`
+    is_element_like(html, "<!--", 7)
 }
 
 fn is_element_like(html: &str, start: &str, expected_smallest_length: usize) -> bool {
@@ -24,27 +25,28 @@ mod test_utils {
 
     #[test]
     fn shohuld_test_end_element() {
-        assert_eq!(true, is_comment_element("<!-- -->"));
-        assert_eq!(true, is_comment_element("<!-- hello -->"));
+        assert!(is_comment_element("<!---->"));
+        assert!(is_comment_element("<!-- -->"));
+        assert!(is_comment_element("<!-- hello -->"));
 
-        assert_eq!(false, is_comment_element("<!--"));
-        assert_eq!(false, is_comment_element("hello"));
-        assert_eq!(false, is_comment_element("</a foo -->"));
+        assert!(!is_comment_element("<!--"));
+        assert!(!is_comment_element("hello"));
+        assert!(!is_comment_element("</a foo -->"));
     }
 }
 
 use crate::elements::Element;
 
 #[derive(PartialEq, Debug)]
-pub struct CommentElement {
-    pub content: String,
+pub struct CommentElement<'a> {
+    pub content: &'a str,
     pub length: usize,
 }
 
-impl Element<CommentElement> for CommentElement {
-    fn extract(html: &str) -> Option<CommentElement> {
+impl<'a> Element<'a, CommentElement<'a>> for CommentElement<'a> {
+    fn extract(html: &'a str) -> Option<CommentElement<'a>> {
         if is_comment_element(html) {
-            let (content, length) = extract_comment_content(html);
+            let (content, length) = extract_comment_content(html)?;
             Some(CommentElement { content, length })
         } else {
             None
@@ -68,10 +70,15 @@ mod test_end_elements {
         let end_element = CommentElement::extract(html);
         assert_eq!(
             Some(CommentElement {
-                content: "div".to_string(),
+                content: "div",
                 length: 10
             }),
             end_element
         );
     }
+    #[test]
+    fn should_return_none_given_a_comment_without_end() {
+        assert_eq!(None, CommentElement::extract("<!-- hello"));
+        assert_eq!(None, CommentElement::extract("<!-->hello"));
+    }
 }
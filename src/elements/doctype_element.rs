@@ -1,7 +1,8 @@
 use crate::elements::utils::extract_element_like;
+use std::borrow::Cow;
 
 /// Parse an starting HTML tag like `<div id'foo' class="bar" hidden aria-label='baz'>`
-fn extract_doctype_content(html: &str) -> (String, usize) {
+fn extract_doctype_content(html: &str) -> Option<(Cow<'_, str>, usize)> {
     extract_element_like(html, "<!doctype", ">")
 }
 
@@ -27,29 +28,29 @@ mod test_utils {
 
     #[test]
     fn shohuld_test_end_element() {
-        assert_eq!(true, is_doctype_element("<!doctype html>"));
-        assert_eq!(true, is_doctype_element("<!DOCTYPE html>"));
-        assert_eq!(true, is_doctype_element("<!doctype html>"));
-        assert_eq!(true, is_doctype_element("<!DOCTYPE html PUBLIC \"-//W3C//DTD XHTML 1.0 Strict//EN\" \"http://www.w3.org/TR/xhtml1/DTD/xhtml1-strict.dtd\">"));
+        assert!(is_doctype_element("<!doctype html>"));
+        assert!(is_doctype_element("<!DOCTYPE html>"));
+        assert!(is_doctype_element("<!doctype html>"));
+        assert!(is_doctype_element("<!DOCTYPE html PUBLIC \"-//W3C//DTD XHTML 1.0 Strict//EN\" \"http://www.w3.org/TR/xhtml1/DTD/xhtml1-strict.dtd\">"));
 
-        assert_eq!(false, is_doctype_element("<!--"));
-        assert_eq!(false, is_doctype_element("hello"));
-        assert_eq!(false, is_doctype_element("</a foo -->"));
+        assert!(!is_doctype_element("<!--"));
+        assert!(!is_doctype_element("hello"));
+        assert!(!is_doctype_element("</a foo -->"));
     }
 }
 
 use crate::elements::Element;
 
 #[derive(PartialEq, Debug)]
-pub struct DoctypeElement {
-    pub content: String,
+pub struct DoctypeElement<'a> {
+    pub content: Cow<'a, str>,
     pub length: usize,
 }
 
-impl Element<DoctypeElement> for DoctypeElement {
-    fn extract(html: &str) -> Option<DoctypeElement> {
+impl<'a> Element<'a, DoctypeElement<'a>> for DoctypeElement<'a> {
+    fn extract(html: &'a str) -> Option<DoctypeElement<'a>> {
         if is_doctype_element(html) {
-            let (content, length) = extract_doctype_content(html);
+            let (content, length) = extract_doctype_content(html)?;
             Some(DoctypeElement { content, length })
         } else {
             None
@@ -73,7 +74,7 @@ mod test_end_elements {
         let end_element = DoctypeElement::extract(html);
         assert_eq!(
             Some(DoctypeElement {
-                content: " html".to_string(),
+                content: Cow::Borrowed(" html"),
                 length: 15
             }),
             end_element
@@ -1,9 +1,16 @@
 use crate::elements::is_element_like;
-use crate::elements::utils::extract_element_like;
+use crate::elements::utils::extract_multiline_element_like;
+use std::borrow::Cow;
 
-/// Parse an starting HTML tag like `<div id'foo' class="bar" hidden aria-label='baz'>`
-fn extract_end_tag_name(html: &str) -> (String, usize) {
-    extract_element_like(html, "</", ">")
+/// Parse an ending HTML tag like `</div>`. The name ends at the first whitespace or `/`, so
+/// `</div >` is the end tag of a `div`.
+fn extract_end_tag_name(html: &str) -> Option<(Cow<'_, str>, usize)> {
+    let (tag_content, length) = extract_multiline_element_like(html, "</", ">")?;
+    let name = tag_content
+        .split(|c: char| c.is_ascii_whitespace() || c == '/')
+        .next()
+        .unwrap_or_default();
+    Some((Cow::Borrowed(name), length))
 }
 
 /// return true if the element starts with `</` and a letter
@@ -18,27 +25,27 @@ mod test_utils {
 
     #[test]
     fn shohuld_test_end_element() {
-        assert_eq!(true, is_end_element("</p>"));
-        assert_eq!(true, is_end_element("</div>"));
+        assert!(is_end_element("</p>"));
+        assert!(is_end_element("</div>"));
 
-        assert_eq!(false, is_end_element("<div>"));
-        assert_eq!(false, is_end_element("hello"));
-        assert_eq!(false, is_end_element("<!-- foo -->"));
+        assert!(!is_end_element("<div>"));
+        assert!(!is_end_element("hello"));
+        assert!(!is_end_element("<!-- foo -->"));
     }
 }
 
 use crate::elements::Element;
 
 #[derive(PartialEq, Debug)]
-pub struct EndElement {
-    pub name: String,
+pub struct EndElement<'a> {
+    pub name: Cow<'a, str>,
     pub length: usize,
 }
 
-impl Element<EndElement> for EndElement {
-    fn extract(html: &str) -> Option<EndElement> {
+impl<'a> Element<'a, EndElement<'a>> for EndElement<'a> {
+    fn extract(html: &'a str) -> Option<EndElement<'a>> {
         if is_end_element(html) {
-            let (name, length) = extract_end_tag_name(html);
+            let (name, length) = extract_end_tag_name(html)?;
             Some(EndElement { name, length })
         } else {
             None
@@ -62,10 +69,28 @@ mod test_end_elements {
         let end_element = EndElement::extract(html);
         assert_eq!(
             Some(EndElement {
-                name: "div".to_string(),
+                name: Cow::Borrowed("div"),
                 length: 6
             }),
             end_element
         );
     }
+
+    #[test]
+    fn should_end_the_name_at_the_first_whitespace() {
+        let html = "</div\n  class='foo'>";
+        let end_element = EndElement::extract(html);
+        assert_eq!(
+            Some(EndElement {
+                name: Cow::Borrowed("div"),
+                length: 20
+            }),
+            end_element
+        );
+        assert_eq!("p", EndElement::extract("</p/>").unwrap().name);
+    }
+    #[test]
+    fn should_return_none_given_an_end_tag_without_end() {
+        assert_eq!(None, EndElement::extract("</div"));
+    }
 }
@@ -1,26 +1,26 @@
+pub mod attributes;
+pub mod cdata_element;
 pub mod comment_element;
 pub mod doctype_element;
 pub mod end_element;
+pub mod processing_instruction_element;
 pub mod start_element;
 pub mod tag_parser;
 pub mod text_element;
 mod utils;
 
-pub trait Element<T> {
-    fn extract(html: &str) -> Option<T>;
+pub trait Element<'a, T> {
+    fn extract(html: &'a str) -> Option<T>;
 }
 
 pub fn is_element_like(html: &str, start: &str, expected_smallest_length: usize) -> bool {
     let has_smallest_length_possible = html.len() >= expected_smallest_length;
     let is_start = html.get(0..start.len()) == Some(start);
 
-    let first_letter_tag_name = html.get(start.len()..start.len() + 1);
+    // the first letter of the tag name may be longer than 1 byte, like `é`
+    let first_letter_tag_name = html.get(start.len()..).and_then(|name| name.chars().next());
     if let Some(first_letter_tag_name) = first_letter_tag_name {
-        let is_alphabetic = first_letter_tag_name
-            .chars()
-            .next()
-            .unwrap()
-            .is_alphabetic();
+        let is_alphabetic = first_letter_tag_name.is_alphabetic();
         has_smallest_length_possible && is_start && is_alphabetic
     } else {
         false
@@ -53,6 +53,12 @@ mod test_elements {
         assert!(!is_valid);
     }
     #[test]
+    fn should_detect_element_like_starting_tag_with_a_non_ascii_letter() {
+        assert!(is_element_like("<été>", "<", 3));
+        assert!(is_element_like("</été>", "</", 4));
+        assert!(!is_element_like("<€>", "<", 3));
+    }
+    #[test]
     fn should_detect_invalid_element_like_starting_tag_too_small() {
         let html = "<a>";
         let start = "<";
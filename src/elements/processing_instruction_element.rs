@@ -0,0 +1,66 @@
+use crate::elements::is_element_like;
+use crate::elements::utils::extract_multiline_element_like;
+
+/// Extract the content of a processing instruction like `<?xml version="1.0"?>`
+fn extract_processing_instruction_content(html: &str) -> Option<(&str, usize)> {
+    extract_multiline_element_like(html, "<?", "?>")
+}
+
+/// return true if the element starts with `<?` and a letter, the first letter of its target
+fn is_processing_instruction_element(html: &str) -> bool {
+    // min length 5, like `<?a?>`
+    is_element_like(html, "<?", 5)
+}
+
+use crate::elements::Element;
+
+/// Processing instruction of an XML document : its content is its target, like `xml`, followed
+/// by its data
+#[derive(PartialEq, Debug)]
+pub struct ProcessingInstructionElement<'a> {
+    pub content: &'a str,
+    pub length: usize,
+}
+
+impl<'a> Element<'a, ProcessingInstructionElement<'a>> for ProcessingInstructionElement<'a> {
+    fn extract(html: &'a str) -> Option<ProcessingInstructionElement<'a>> {
+        if is_processing_instruction_element(html) {
+            let (content, length) = extract_processing_instruction_content(html)?;
+            Some(ProcessingInstructionElement { content, length })
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_processing_instruction_element {
+    use super::*;
+
+    #[test]
+    fn should_extract_the_content_of_a_processing_instruction() {
+        assert_eq!(
+            Some(ProcessingInstructionElement {
+                content: "xml version=\"1.0\" ",
+                length: 22
+            }),
+            ProcessingInstructionElement::extract("<?xml version=\"1.0\" ?><rss>")
+        );
+        assert_eq!(
+            Some(ProcessingInstructionElement {
+                content: "php echo $a > 1; ",
+                length: 21
+            }),
+            ProcessingInstructionElement::extract("<?php echo $a > 1; ?>")
+        );
+    }
+
+    #[test]
+    fn should_return_none_given_an_invalid_processing_instruction() {
+        assert_eq!(
+            None,
+            ProcessingInstructionElement::extract("<?xml version='1.0'>")
+        );
+        assert_eq!(None, ProcessingInstructionElement::extract("<? 1 ?>"));
+    }
+}
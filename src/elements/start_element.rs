@@ -6,17 +6,50 @@ fn is_start_element(html: &str) -> bool {
     is_element_like(html, "<", 3)
 }
 
-use crate::elements::tag_parser::TagParser;
-use std::cmp;
-use std::collections::HashMap;
+use crate::elements::attributes::{Attribute, Attributes};
+use crate::elements::tag_parser::{AttributeIterator, TagParser};
+
+/// Elements that never have content nor end tag, even when written without `/>`, like `<br>`
+const VOID_ELEMENTS: [&str; 13] = [
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "source", "track",
+    "wbr",
+];
+
+/// Returns true if the element never has content nor end tag, like `br` or `img`
+pub fn is_void_element(name: &str) -> bool {
+    VOID_ELEMENTS
+        .iter()
+        .any(|void_element| name.eq_ignore_ascii_case(void_element))
+}
+
+/// Split a qualified name into its namespace prefix and its local name, like `xlink:href` into
+/// `xlink` and `href`. A name without `:` has no prefix.
+pub fn split_qualified_name(name: &str) -> (Option<&str>, &str) {
+    match name.split_once(':') {
+        Some((prefix, local_name)) if !prefix.is_empty() && !local_name.is_empty() => {
+            (Some(prefix), local_name)
+        }
+        _ => (None, name),
+    }
+}
+
+/// Lowercase the ASCII letters of `name`, keeping its spelling in `original_name` if it changes
+pub(crate) fn fold_name_case(name: &mut str, original_name: &mut Option<String>) {
+    if name.bytes().any(|b| b.is_ascii_uppercase()) {
+        *original_name = Some(name.to_string());
+        name.make_ascii_lowercase();
+    }
+}
 
 /// hold elements of an HTML tag
 #[derive(PartialEq, Debug)]
 pub struct Tag {
     /// tag name
     pub name: String,
-    /// attributes map : attributes name -> attributes value
-    pub attributes: HashMap<String, String>,
+    /// tag name as written in the HTML code, like `TD`, when `name` was lowercased
+    pub original_name: Option<String>,
+    /// attributes in the order of the HTML code
+    pub attributes: Attributes,
     /// length of the whole tag from `<` to `>`
     pub length: usize,
     pub is_autoclosing: bool,
@@ -24,77 +57,245 @@ pub struct Tag {
 
 impl Tag {
     /// Get the tag id from the attributes
-    pub fn id(&self) -> Option<&String> {
+    pub fn id(&self) -> Option<&str> {
         self.attributes.get("id")
     }
     /// Get the tag class from the attributes. It is the class bulk value : a
     /// string where each classes are separated by spaces, like in HTML code.
-    pub fn classes(&self) -> Option<&String> {
+    pub fn classes(&self) -> Option<&str> {
         self.attributes.get("class")
     }
+    /// Returns true if the tag has no end tag : it is written like `<div/>` or it is a void
+    /// element like `<br>`
+    pub fn is_self_closing(&self) -> bool {
+        self.is_autoclosing || is_void_element(&self.name)
+    }
+    /// Namespace prefix of the tag name, like `svg` for `<svg:rect>`
+    pub fn prefix(&self) -> Option<&str> {
+        split_qualified_name(&self.name).0
+    }
+    /// Tag name without its namespace prefix, like `rect` for `<svg:rect>`
+    pub fn local_name(&self) -> &str {
+        split_qualified_name(&self.name).1
+    }
+    /// Tag name as written in the HTML code
+    pub fn written_name(&self) -> &str {
+        self.original_name.as_deref().unwrap_or(&self.name)
+    }
+    /// Lowercase the ASCII letters of the tag name and of the attribute names, like an HTML
+    /// parser does. The names as written are kept in `original_name`.
+    pub fn fold_case(&mut self) {
+        fold_name_case(&mut self.name, &mut self.original_name);
+        self.attributes.iter_mut().for_each(Attribute::fold_case);
+    }
+    /// Replace the tag with the borrowed tag `tag`, keeping the memory of its strings. The
+    /// attributes replaced are moved into `unused_attributes`, to be reused by the next tags.
+    pub(crate) fn refill(&mut self, tag: TagRef<'_>, unused_attributes: &mut Vec<Attribute>) {
+        self.name.clear();
+        self.name.push_str(tag.name);
+        self.original_name = None;
+        self.attributes.move_into(unused_attributes);
+        // the attributes are after the `<` and the tag name
+        TagParser::new().parse_attributes_into(
+            tag.attributes_html,
+            1 + tag.name.len(),
+            &mut self.attributes,
+            unused_attributes,
+        );
+        self.length = tag.length;
+        self.is_autoclosing = tag.is_autoclosing;
+    }
 }
-/// Parse an starting HTML tag like `<div id'foo' class="bar" hidden aria-label='baz'>`
-pub fn extract_tag_name(html: &str) -> Option<Tag> {
-    let start = html.find('<').unwrap();
 
-    let end_autoclosing = html.find("/>");
-    let mut is_autoclosing_tag = end_autoclosing.is_some(); // FIXME
+/// Borrowed version of an HTML tag : the tag name and the attributes are slices of the HTML
+/// code. Attributes are only parsed when they are read.
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub struct TagRef<'a> {
+    /// tag name
+    pub name: &'a str,
+    /// HTML code after the tag name, containing the attributes
+    pub attributes_html: &'a str,
+    /// length of the whole tag from `<` to `>`
+    pub length: usize,
+    pub is_autoclosing: bool,
+}
 
-    let end_closing = html.find('>');
+impl<'a> TagRef<'a> {
+    /// Iterate over the attributes : attribute name and attribute value
+    pub fn attributes(&self) -> AttributeIterator<'a> {
+        AttributeIterator::new(self.attributes_html)
+    }
+    /// Get the value of an attribute. When the attribute is repeated, the first value is returned.
+    /// An attribute without value has an empty value.
+    pub fn attribute(&self, name: &str) -> Option<&'a str> {
+        self.attributes()
+            .find(|(attribute_name, _)| *attribute_name == name)
+            .map(|(_, value)| value.unwrap_or_default())
+    }
+    /// Get the tag id from the attributes
+    pub fn id(&self) -> Option<&'a str> {
+        self.attribute("id")
+    }
+    /// Get the tag class from the attributes. It is the class bulk value : a
+    /// string where each classes are separated by spaces, like in HTML code.
+    pub fn classes(&self) -> Option<&'a str> {
+        self.attribute("class")
+    }
+    /// Returns true if the tag has no end tag : it is written like `<div/>` or it is a void
+    /// element like `<br>`
+    pub fn is_self_closing(&self) -> bool {
+        self.is_autoclosing || is_void_element(self.name)
+    }
+    /// Namespace prefix of the tag name, like `svg` for `<svg:rect>`
+    pub fn prefix(&self) -> Option<&'a str> {
+        split_qualified_name(self.name).0
+    }
+    /// Tag name without its namespace prefix, like `rect` for `<svg:rect>`
+    pub fn local_name(&self) -> &'a str {
+        split_qualified_name(self.name).1
+    }
+}
 
-    let end = match (end_autoclosing, end_closing) {
-        (Some(end_autoclosing), Some(end_closing)) => {
-            is_autoclosing_tag = end_autoclosing < end_closing;
-            cmp::min(end_autoclosing, end_closing)
+impl From<TagRef<'_>> for Tag {
+    fn from(tag: TagRef<'_>) -> Self {
+        Tag {
+            name: tag.name.to_string(),
+            original_name: None,
+            // the attributes are after the `<` and the tag name
+            attributes: TagParser::new().parse_attributes(tag.attributes_html, 1 + tag.name.len()),
+            length: tag.length,
+            is_autoclosing: tag.is_autoclosing,
         }
-        (Some(end_autoclosing), None) => end_autoclosing,
-        (None, Some(end_closing)) => end_closing,
-        _ => return None,
-    };
+    }
+}
 
-    let tag_content = html.get(start + 1..end).unwrap();
+/// Parse an starting HTML tag like `<div id'foo' class="bar" hidden aria-label='baz'>`
+pub fn extract_tag_name(html: &str) -> Option<Tag> {
+    extract_tag_ref(html).map(Tag::from)
+}
 
-    let tag_content: String = tag_content
-        .replace("\n\r", " ")
-        .replace("\n", " ")
-        .replace("\r", " ");
+/// Parse an starting HTML tag like `<div id'foo' class="bar" hidden aria-label='baz'>`
+/// without copying the HTML code.
+pub fn extract_tag_ref(html: &str) -> Option<TagRef<'_>> {
+    let start = html.find('<')?;
 
-    let start_attributes_index = tag_content.find(' ');
+    // only the tag is read : the search stops at the `>` ending the tag
+    let mut tag_end_search = TagEndSearch::default();
+    let end_closing = start + tag_end_search.find(html.get(start..)?)?;
 
-    let end_attributes_index = tag_content.len();
+    // a `/` ending an unquoted attribute value, like in `<a href=foo/>`, is part of the value
+    let is_autoclosing_tag = end_closing > start + 1
+        && html.as_bytes()[end_closing - 1] == b'/'
+        && !tag_end_search.is_in_unquoted_value;
 
-    let attributes = if let Some(start_attributes_index) = start_attributes_index {
-        let attributes_code = tag_content
-            .get(start_attributes_index..end_attributes_index)
-            .unwrap_or_default();
-        let mut tag_parser = TagParser::new();
-        tag_parser.parse_attributes(attributes_code)
+    let end = if is_autoclosing_tag {
+        end_closing - 1
     } else {
-        HashMap::new()
+        end_closing
     };
 
-    let offset = if is_autoclosing_tag { 2 } else { 1 };
-
-    let name = tag_content
-        .find(' ')
-        .and_then(|position| tag_content.get(0..position));
+    let tag_content = html.get(start + 1..end)?;
 
-    let name = if let Some(name) = name {
-        name.to_string()
-    } else {
-        tag_content
+    let (name, attributes_html) = match tag_content.find(char::is_whitespace) {
+        Some(position) => tag_content.split_at(position),
+        None => (tag_content, ""),
     };
 
-    Some(Tag {
+    let offset = if is_autoclosing_tag { 2 } else { 1 };
+
+    Some(TagRef {
         name,
-        attributes,
+        attributes_html,
         length: end - start + offset,
         is_autoclosing: is_autoclosing_tag,
     })
 }
 
-impl Element<Tag> for Tag {
-    fn extract(html: &str) -> Option<Tag> {
+/// Find the position of the `>` ending the tag at the beginning of `html`. A `>` inside a quoted
+/// attribute value, like `<a title="1 > 0">`, does not end the tag.
+pub fn find_tag_end(html: &str) -> Option<usize> {
+    TagEndSearch::default().find(html)
+}
+
+#[cfg(test)]
+thread_local! {
+    /// count of the bytes read by the searches of the tag ends of the current thread
+    static SEARCHED_BYTE_COUNT: std::cell::Cell<usize> = const { std::cell::Cell::new(0) };
+}
+
+/// Count of the bytes read by the searches of the tag ends of the current thread, to check that
+/// the HTML code is read in linear time
+#[cfg(test)]
+pub(crate) fn searched_byte_count() -> usize {
+    SEARCHED_BYTE_COUNT.with(std::cell::Cell::get)
+}
+
+#[cfg(test)]
+fn count_searched_bytes(byte_count: usize) {
+    SEARCHED_BYTE_COUNT.with(|count| count.set(count.get() + byte_count));
+}
+
+/// Search of the `>` ending a tag, that is resumed when more HTML code is received instead of
+/// reading the whole tag again
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct TagEndSearch {
+    /// length of the HTML code already searched
+    searched_length: usize,
+    /// quote of the attribute value being read
+    quote: Option<u8>,
+    /// true if the last character, apart from white spaces, is the `=` before an attribute value
+    is_after_equals: bool,
+    /// true if the last character is in an unquoted attribute value, like `foo/` in
+    /// `<a href=foo/>`
+    is_in_unquoted_value: bool,
+}
+
+impl TagEndSearch {
+    /// Find the position of the `>` ending the tag at the beginning of `html`, reading only the
+    /// HTML code after the one already searched : `html` must start with it.
+    pub(crate) fn find(&mut self, html: &str) -> Option<usize> {
+        let unsearched_html = html
+            .as_bytes()
+            .get(self.searched_length..)
+            .unwrap_or_default();
+        for (index, &c) in unsearched_html.iter().enumerate() {
+            match self.quote {
+                Some(opening_quote) => {
+                    if c == opening_quote {
+                        self.quote = None;
+                    }
+                }
+                None => match c {
+                    b'>' => {
+                        #[cfg(test)]
+                        count_searched_bytes(index + 1);
+                        return Some(self.searched_length + index);
+                    }
+                    c if c.is_ascii_whitespace() => self.is_in_unquoted_value = false,
+                    // an unquoted value, like `a=b'c`, ends at the next white space or `>`
+                    _ if self.is_in_unquoted_value => {}
+                    b'"' | b'\'' if self.is_after_equals => {
+                        self.quote = Some(c);
+                        self.is_after_equals = false;
+                    }
+                    _ if self.is_after_equals => {
+                        self.is_after_equals = false;
+                        self.is_in_unquoted_value = true;
+                    }
+                    b'=' => self.is_after_equals = true,
+                    _ => {}
+                },
+            }
+        }
+        #[cfg(test)]
+        count_searched_bytes(unsearched_html.len());
+        self.searched_length = html.len();
+        None
+    }
+}
+
+impl<'a> Element<'a, Tag> for Tag {
+    fn extract(html: &'a str) -> Option<Tag> {
         if is_start_element(html) {
             extract_tag_name(html)
         } else {
@@ -103,19 +304,48 @@ impl Element<Tag> for Tag {
     }
 }
 
+impl<'a> Element<'a, TagRef<'a>> for TagRef<'a> {
+    fn extract(html: &'a str) -> Option<TagRef<'a>> {
+        if is_start_element(html) {
+            extract_tag_ref(html)
+        } else {
+            None
+        }
+    }
+}
+
 #[cfg(test)]
 mod test_utils {
     use super::*;
     #[test]
     fn should_test_start_element() {
-        assert_eq!(true, is_start_element("<a>"));
-        assert_eq!(true, is_start_element("<div>"));
-        assert_eq!(true, is_start_element("<br>"));
-        assert_eq!(true, is_start_element("<br/>"));
+        assert!(is_start_element("<a>"));
+        assert!(is_start_element("<div>"));
+        assert!(is_start_element("<br>"));
+        assert!(is_start_element("<br/>"));
+
+        assert!(!is_start_element("hello"));
+        assert!(!is_start_element("<123"));
+        assert!(!is_start_element("</p>"));
+    }
 
-        assert_eq!(false, is_start_element("hello"));
-        assert_eq!(false, is_start_element("<123"));
-        assert_eq!(false, is_start_element("</p>"));
+    #[test]
+    fn should_resume_the_search_of_the_tag_end_in_a_quoted_value() {
+        let mut search = TagEndSearch::default();
+        assert_eq!(None, search.find("<a title='"));
+        // the `>` is in the quoted value started in the HTML code already searched
+        assert_eq!(Some(12), search.find("<a title='>'>"));
+    }
+
+    #[test]
+    fn should_split_a_qualified_name() {
+        assert_eq!((Some("svg"), "rect"), split_qualified_name("svg:rect"));
+        assert_eq!(
+            (None, "linearGradient"),
+            split_qualified_name("linearGradient")
+        );
+        assert_eq!((None, ":rect"), split_qualified_name(":rect"));
+        assert_eq!((None, "svg:"), split_qualified_name("svg:"));
     }
 }
 
@@ -167,15 +397,15 @@ mod tests {
     fn should_extract_zero_html_class() {
         let html = "<div >";
         let tag = extract_tag_name(html).unwrap();
-        assert!(tag.attributes.get("class").is_none());
+        assert!(!tag.attributes.contains_key("class"));
         assert_eq!(html.len(), tag.length);
     }
     #[test]
     fn should_extract_one_html_class() {
         let html = "<div  class=\"bar\" >";
         let tag = extract_tag_name(html).unwrap();
-        assert!(tag.attributes.get("class").is_some());
-        assert_eq!(Some(&String::from("bar")), tag.attributes.get("class"));
+        assert!(tag.attributes.contains_key("class"));
+        assert_eq!(Some("bar"), tag.attributes.get("class"));
         assert_eq!(html.len(), tag.length);
     }
     #[test]
@@ -183,14 +413,24 @@ mod tests {
         let html = "<div  class='bar   baz   foo mun' >";
         let tag = extract_tag_name(html).unwrap();
         assert_eq!("div", tag.name);
-        assert!(tag.attributes.get("class").is_some());
-        assert_eq!(
-            Some(&String::from("bar   baz   foo mun")),
-            tag.attributes.get("class")
-        );
+        assert!(tag.attributes.contains_key("class"));
+        assert_eq!(Some("bar   baz   foo mun"), tag.attributes.get("class"));
         assert_eq!(html.len(), tag.length);
     }
 
+    #[test]
+    fn should_lowercase_the_names_and_keep_their_spelling() {
+        let mut tag = extract_tag_name("<TD VAlign='Top' colspan=2>").unwrap();
+        tag.fold_case();
+        assert_eq!("td", tag.name);
+        assert_eq!("TD", tag.written_name());
+        assert_eq!(Some("Top"), tag.attributes.get("valign"));
+        let valign = tag.attributes.get_attribute("valign").unwrap();
+        assert_eq!("VAlign", valign.written_name());
+        let colspan = tag.attributes.get_attribute("colspan").unwrap();
+        assert_eq!(None, colspan.original_name);
+    }
+
     #[test]
     fn should_extract_consider_id_and_class_as_attributes() {
         let html = "<div id='foo' class='bar'>";
@@ -234,4 +474,91 @@ mod tests {
         let tag = extract_tag_name("<br");
         assert_eq!(None, tag);
     }
+
+    #[test]
+    fn should_extract_a_tag_borrowing_the_html_code() {
+        let html = "<a  href='/foo' hidden>bar</a>";
+        let tag = extract_tag_ref(html).unwrap();
+        assert_eq!("a", tag.name);
+        assert_eq!(Some("/foo"), tag.attribute("href"));
+        assert_eq!(Some(""), tag.attribute("hidden"));
+        assert_eq!(None, tag.id());
+        assert_eq!(23, tag.length);
+    }
+
+    #[test]
+    fn should_refill_a_tag_like_a_new_tag() {
+        let mut tag = extract_tag_name("<TD hidden title='foo' colspan=2>").unwrap();
+        tag.fold_case();
+        let mut unused_attributes = vec![];
+        for html in [
+            "<a  href='/foo' hidden>",
+            "<br/>",
+            "<p title id=\"x\" a b c>",
+        ] {
+            let tag_ref = extract_tag_ref(html).unwrap();
+            tag.refill(tag_ref, &mut unused_attributes);
+            assert_eq!(Tag::from(tag_ref), tag);
+        }
+    }
+
+    #[test]
+    fn should_not_end_the_tag_inside_a_quoted_attribute_value() {
+        let html = "<a title=\"1 > 0\" href='x'>foo</a>";
+        let tag = extract_tag_name(html).unwrap();
+        assert_eq!("a", tag.name);
+        assert_eq!(Some("1 > 0"), tag.attributes.get("title"));
+        assert_eq!(Some("x"), tag.attributes.get("href"));
+        assert_eq!(26, tag.length);
+        assert!(!tag.is_autoclosing);
+    }
+    #[test]
+    fn should_not_be_autoclosing_given_slash_inside_a_quoted_attribute_value() {
+        let html = "<img src=\"a/>b\">";
+        let tag = extract_tag_name(html).unwrap();
+        assert_eq!("img", tag.name);
+        assert_eq!(Some("a/>b"), tag.attributes.get("src"));
+        assert_eq!(html.len(), tag.length);
+        assert!(!tag.is_autoclosing);
+
+        let html = "<img src = 'a>b' />";
+        let tag = extract_tag_name(html).unwrap();
+        assert_eq!(Some("a>b"), tag.attributes.get("src"));
+        assert_eq!(html.len(), tag.length);
+        assert!(tag.is_autoclosing);
+    }
+    #[test]
+    fn should_not_be_autoclosing_given_slash_ending_an_unquoted_attribute_value() {
+        let tag = extract_tag_ref("<a href=foo/>x</a>").unwrap();
+        assert_eq!(" href=foo/", tag.attributes_html);
+        assert_eq!(13, tag.length);
+        assert!(!tag.is_autoclosing);
+        assert_eq!(Some("foo/"), Tag::from(tag).attributes.get("href"));
+
+        let tag = extract_tag_ref("<a title=a'b>c'>").unwrap();
+        assert_eq!(" title=a'b", tag.attributes_html);
+
+        for html in ["<a href=foo />", "<a href='foo'/>", "<a hidden/>", "<br/>"] {
+            let tag = extract_tag_ref(html).unwrap();
+            assert!(tag.is_autoclosing, "{}", html);
+            assert_eq!(html.len(), tag.length);
+        }
+    }
+    #[test]
+    fn should_not_extract_a_tag_with_an_unterminated_quoted_attribute_value() {
+        assert_eq!(None, extract_tag_name("<a title='1 > 0>"));
+    }
+
+    #[test]
+    fn should_be_self_closing_given_a_void_element_or_an_autoclosing_tag() {
+        assert!(extract_tag_name("<br>").unwrap().is_self_closing());
+        assert!(extract_tag_name("<IMG src='a.png'>")
+            .unwrap()
+            .is_self_closing());
+        assert!(extract_tag_name("<div/>").unwrap().is_self_closing());
+        assert!(!extract_tag_name("<div>").unwrap().is_self_closing());
+        assert!(extract_tag_ref("<input type=text>")
+            .unwrap()
+            .is_self_closing());
+    }
 }
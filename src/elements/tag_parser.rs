@@ -1,18 +1,24 @@
 use std::cmp::PartialEq;
-use std::collections::HashMap;
+use std::ops::Range;
+use std::str::CharIndices;
+
+use crate::elements::attributes::{Attribute, AttributeQuote, Attributes};
 
 /// represent the state when parsing elements inside an HTML tag
 #[derive(Debug)]
 pub struct TagParser {
-    /// temporary content for the attribute name
-    attribute_name_tmp: String,
-    /// temporary content for the attribute value
-    attribute_value_tmp: String,
+    /// position of the first character of the attribute name
+    attribute_name_start: usize,
+    /// position after the last character of the attribute name
+    attribute_name_end: usize,
+    /// position of the first character of the attribute value
+    attribute_value_start: usize,
     /// state when reading the content of an HTML tag
     state: ReadingState,
 }
 
-/// state of the parser when reading inside an HTML tag
+/// state of the parser when reading inside an HTML tag. The states follow the HTML tokenizer
+/// states from the "before attribute name" state to the "attribute value" states.
 #[derive(PartialEq, Debug)]
 enum ReadingState {
     /// First state and initial state when before reading a (new) attribute.
@@ -24,8 +30,19 @@ enum ReadingState {
     WaitForEquals,
     /// The parser found the `=` character and expect to read the attribute value.
     WaitForAttributeValue,
-    /// The parser is reading the attribute value.
-    ReadingAttributeValue,
+    /// The parser is reading an attribute value between the given quote, `"` or `'`.
+    ReadingQuotedAttributeValue(char),
+    /// The parser is reading an attribute value without quotes, like `width=100`.
+    ReadingUnquotedAttributeValue,
+}
+
+/// position of an attribute into the HTML code read by the parser
+#[derive(PartialEq, Debug)]
+struct AttributeSpan {
+    name: Range<usize>,
+    /// `None` when the attribute has no value
+    value: Option<Range<usize>>,
+    quote: AttributeQuote,
 }
 
 /// implementation of a tag parser
@@ -33,94 +50,234 @@ impl TagParser {
     /// initialize the parser
     pub fn new() -> Self {
         TagParser {
-            attribute_name_tmp: String::new(),
-            attribute_value_tmp: String::new(),
+            attribute_name_start: 0,
+            attribute_name_end: 0,
+            attribute_value_start: 0,
             state: ReadingState::Start,
         }
     }
 
     /// Parse all attributes after the tag name. It read attributes character by character.
-    pub fn parse_attributes(&mut self, html: &str) -> HashMap<String, String> {
-        let mut attributes = HashMap::new();
+    /// `offset` is the position of `html` from the `<` of the tag : it is added to the spans of
+    /// the attributes.
+    pub fn parse_attributes(&mut self, html: &str, offset: usize) -> Attributes {
+        let mut attributes = Attributes::new();
+        self.parse_attributes_into(html, offset, &mut attributes, &mut Vec::new());
+        attributes
+    }
+
+    /// Parse all attributes after the tag name like `parse_attributes`, adding them to
+    /// `attributes`. The strings of the `unused` attributes are reused, so the attributes of the
+    /// next tags are parsed without allocating once the strings are long enough.
+    pub(crate) fn parse_attributes_into(
+        &mut self,
+        html: &str,
+        offset: usize,
+        attributes: &mut Attributes,
+        unused: &mut Vec<Attribute>,
+    ) {
+        let mut iterator = AttributeIterator::new(html);
+        while let Some(span) = iterator.next_span() {
+            let (name, value) = iterator.slices(&span);
+            let shift = |range: Range<usize>| range.start + offset..range.end + offset;
+            let name_span = shift(span.name);
+            let value_span = span.value.map(shift);
+            let attribute = match unused.pop() {
+                Some(mut attribute) => {
+                    replace_string(&mut attribute.name, name);
+                    attribute.original_name = None;
+                    match (&mut attribute.value, value) {
+                        (Some(unused_value), Some(value)) => replace_string(unused_value, value),
+                        (unused_value, value) => *unused_value = value.map(str::to_string),
+                    }
+                    attribute.name_span = name_span;
+                    attribute.value_span = value_span;
+                    attribute.quote = span.quote;
+                    attribute
+                }
+                None => Attribute {
+                    name: name.to_string(),
+                    original_name: None,
+                    value: value.map(str::to_string),
+                    name_span,
+                    value_span,
+                    quote: span.quote,
+                },
+            };
+            attributes.push(attribute);
+        }
+    }
 
+    /// Read the character `c` at the position `index` of `html`. It returns the attribute
+    /// completed by this character, if any.
+    fn read_char(&mut self, index: usize, c: char) -> Option<AttributeSpan> {
         use ReadingState::*;
 
-        html.chars().for_each(|c| {
-            //println!("c = {:?}, parser = {:?}", c, self);
-
-            if is_attribute_name(&c) && (self.state == Start || self.state == ReadingAttributeName)
-            {
-                self.attribute_name_tmp.push(c);
-                self.state = ReadingAttributeName;
-            } else if is_attribute_name(&c) && self.state == ReadingAttributeName {
-                self.attribute_name_tmp.push(c);
-            } else if c == '='
-                && (self.state == ReadingAttributeName || self.state == WaitForEquals)
-            {
-                self.state = WaitForAttributeValue;
-            } else if is_quote(&c) && self.state == WaitForAttributeValue {
-                // next is the value
-                self.state = ReadingAttributeValue;
-            } else if !is_quote(&c) && self.state == ReadingAttributeValue {
-                self.attribute_value_tmp.push(c);
-            } else if self.is_last_quote_ending_reading_attribute_value(&c) {
-                // end of attributes reading
-                // 1. storing
-                attributes.insert(
-                    self.attribute_name_tmp.clone(),
-                    self.attribute_value_tmp.clone(),
-                );
-                // 2. cleaning
-                self.re_initialise_state();
-            } else if c.is_whitespace() && self.state == ReadingAttributeName {
-                self.state = WaitForEquals
-            } else if is_attribute_name(&c) && self.state == WaitForEquals {
-                // end of attributes reading
-                // 1. storing
-                attributes.insert(self.attribute_name_tmp.clone(), String::from("true"));
-                // 2. cleaning
-                self.re_initialise_state();
-                // 3. init state
-                self.attribute_name_tmp.push(c);
+        let next_index = index + c.len_utf8();
+
+        match self.state {
+            Start => {
+                if !is_attribute_separator(c) {
+                    self.start_attribute_name(index, next_index);
+                }
+            }
+            ReadingAttributeName => match c {
+                '=' => self.state = WaitForAttributeValue,
+                '/' => return Some(self.end_attribute_without_value()),
+                c if c.is_ascii_whitespace() => self.state = WaitForEquals,
+                _ => self.attribute_name_end = next_index,
+            },
+            WaitForEquals => match c {
+                '=' => self.state = WaitForAttributeValue,
+                '/' => return Some(self.end_attribute_without_value()),
+                c if c.is_ascii_whitespace() => {}
+                _ => {
+                    // the attribute has no value, the next attribute starts
+                    let attribute = self.end_attribute_without_value();
+                    self.start_attribute_name(index, next_index);
+                    return Some(attribute);
+                }
+            },
+            WaitForAttributeValue => match c {
+                c if c.is_ascii_whitespace() => {}
+                c if is_quote(&c) => {
+                    // next is the value
+                    self.attribute_value_start = next_index;
+                    self.state = ReadingQuotedAttributeValue(c);
+                }
+                _ => {
+                    self.attribute_value_start = index;
+                    self.state = ReadingUnquotedAttributeValue;
+                }
+            },
+            ReadingQuotedAttributeValue(quote) => {
+                if c == quote {
+                    return Some(self.end_attribute(index));
+                }
+            }
+            ReadingUnquotedAttributeValue => {
+                if c.is_ascii_whitespace() {
+                    return Some(self.end_attribute(index));
+                }
             }
-        });
-
-        // if it has attribute name without a value, then store the attribute with a true value
-        if !self.attribute_name_tmp.is_empty()
-            && self.attribute_value_tmp.is_empty()
-            && (self.state == ReadingAttributeName || self.state == WaitForEquals)
-        {
-            // 1. storing
-            attributes.insert(self.attribute_name_tmp.clone(), String::from("true"));
-            // 2. cleaning
-            self.re_initialise_state();
         }
+        None
+    }
 
-        attributes
+    /// Read the end of the HTML code : it returns the last attribute if it is not complete yet
+    fn read_end(&mut self, html: &str) -> Option<AttributeSpan> {
+        use ReadingState::*;
+
+        match self.state {
+            Start => None,
+            ReadingAttributeName | WaitForEquals => Some(self.end_attribute_without_value()),
+            WaitForAttributeValue => {
+                // like `<a href=>` : the value is empty
+                self.attribute_value_start = html.len();
+                self.state = ReadingUnquotedAttributeValue;
+                Some(self.end_attribute(html.len()))
+            }
+            ReadingQuotedAttributeValue(_) | ReadingUnquotedAttributeValue => {
+                Some(self.end_attribute(html.len()))
+            }
+        }
+    }
+
+    fn start_attribute_name(&mut self, index: usize, next_index: usize) {
+        self.attribute_name_start = index;
+        self.attribute_name_end = next_index;
+        self.state = ReadingState::ReadingAttributeName;
     }
 
-    /// Initialise the parser in order to read the next attribute.
-    /// It cleans tmp variables and re-initialize the parser state
-    fn re_initialise_state(&mut self) {
-        self.attribute_name_tmp = String::new();
-        self.attribute_value_tmp = String::new();
+    /// End the attribute whose value ends at `value_end`
+    fn end_attribute(&mut self, value_end: usize) -> AttributeSpan {
+        let quote = match self.state {
+            ReadingState::ReadingQuotedAttributeValue('"') => AttributeQuote::Double,
+            ReadingState::ReadingQuotedAttributeValue(_) => AttributeQuote::Single,
+            _ => AttributeQuote::None,
+        };
         self.state = ReadingState::Start;
+        AttributeSpan {
+            name: self.attribute_name_start..self.attribute_name_end,
+            value: Some(self.attribute_value_start..value_end),
+            quote,
+        }
     }
 
-    fn is_last_quote_ending_reading_attribute_value(&self, c: &char) -> bool {
-        is_quote(&c) && self.state == ReadingState::ReadingAttributeValue
+    /// End an attribute without value, like `hidden`
+    fn end_attribute_without_value(&mut self) -> AttributeSpan {
+        self.state = ReadingState::Start;
+        AttributeSpan {
+            name: self.attribute_name_start..self.attribute_name_end,
+            value: None,
+            quote: AttributeQuote::None,
+        }
     }
 }
 
-/// Returns true in the case the letter belongs to the allowed letter for attribute names.
-fn is_attribute_name(c: &char) -> bool {
-    c.is_alphanumeric() || *c == '-'
+/// Iterator over the attributes of an HTML tag, read character by character. Attribute names and
+/// values are slices of the HTML code. An attribute without value, like `hidden`, has no value.
+/// Repeated attributes are all returned, in the order of the HTML code.
+pub struct AttributeIterator<'a> {
+    html: &'a str,
+    chars: CharIndices<'a>,
+    parser: TagParser,
+}
+
+impl<'a> AttributeIterator<'a> {
+    /// `html` is the HTML code after the tag name
+    pub fn new(html: &'a str) -> Self {
+        AttributeIterator {
+            html,
+            chars: html.char_indices(),
+            parser: TagParser::new(),
+        }
+    }
+
+    /// Read the position of the next attribute
+    fn next_span(&mut self) -> Option<AttributeSpan> {
+        for (index, c) in self.chars.by_ref() {
+            if let Some(span) = self.parser.read_char(index, c) {
+                return Some(span);
+            }
+        }
+        self.parser.read_end(self.html)
+    }
+
+    /// Name and value of an attribute read from its position
+    fn slices(&self, span: &AttributeSpan) -> (&'a str, Option<&'a str>) {
+        let name = self.html.get(span.name.clone()).unwrap_or_default();
+        let value = span
+            .value
+            .as_ref()
+            .map(|value| self.html.get(value.clone()).unwrap_or_default());
+        (name, value)
+    }
+}
+
+impl<'a> Iterator for AttributeIterator<'a> {
+    type Item = (&'a str, Option<&'a str>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_span().map(|span| self.slices(&span))
+    }
+}
+
+/// Returns true if the character separates 2 attributes : a white space or a `/`.
+fn is_attribute_separator(c: char) -> bool {
+    c.is_ascii_whitespace() || c == '/'
 }
 /// Returns true if the character is `"` or a `'`.
 fn is_quote(c: &char) -> bool {
     *c == '"' || *c == '\''
 }
 
+/// Replace the content of `string` with `value`, keeping the memory of `string`
+fn replace_string(string: &mut String, value: &str) {
+    string.clear();
+    string.push_str(value);
+}
+
 #[cfg(test)]
 mod tag_parser_test {
 
@@ -130,7 +287,7 @@ mod tag_parser_test {
     fn should_return_empty_attributes_map_given_empty_str() {
         let html = "";
         let mut parser = TagParser::new();
-        let attributes = parser.parse_attributes(html);
+        let attributes = parser.parse_attributes(html, 0);
         assert!(attributes.is_empty());
     }
 
@@ -139,30 +296,130 @@ mod tag_parser_test {
         let html = " foo='bar' hidden = 'true' ";
         println!("Parsing : {:?}", html);
         let mut parser = TagParser::new();
-        let attributes = parser.parse_attributes(html);
+        let attributes = parser.parse_attributes(html, 0);
         assert!(!attributes.is_empty());
-        assert_eq!(Some(&String::from("bar")), attributes.get("foo"));
-        assert_eq!(Some(&String::from("true")), attributes.get("hidden"));
+        assert_eq!(Some("bar"), attributes.get("foo"));
+        assert_eq!(Some("true"), attributes.get("hidden"));
+        assert!(!attributes.get_attribute("hidden").unwrap().is_boolean());
     }
 
     #[test]
     fn should_read_an_attribute_without_value_at_last_position_of_the_html_code() {
         let html = " foo='bar' hidden ";
         let mut parser = TagParser::new();
-        let attributes = parser.parse_attributes(html);
+        let attributes = parser.parse_attributes(html, 0);
         assert!(!attributes.is_empty());
-        assert_eq!(Some(&String::from("bar")), attributes.get("foo"));
-        assert_eq!(Some(&String::from("true")), attributes.get("hidden"));
+        assert_eq!(Some("bar"), attributes.get("foo"));
+        assert!(attributes.get_attribute("hidden").unwrap().is_boolean());
     }
 
     #[test]
     fn should_read_an_attribute_without_value_in_the_middle_position_of_the_html_code() {
         let html = " foo='bar' hidden class='title'";
         let mut parser = TagParser::new();
-        let attributes = parser.parse_attributes(html);
+        let attributes = parser.parse_attributes(html, 0);
         assert!(!attributes.is_empty());
-        assert_eq!(Some(&String::from("bar")), attributes.get("foo"));
-        assert_eq!(Some(&String::from("true")), attributes.get("hidden"));
-        assert_eq!(Some(&String::from("title")), attributes.get("class"));
+        assert_eq!(Some("bar"), attributes.get("foo"));
+        assert!(attributes.get_attribute("hidden").unwrap().is_boolean());
+        assert_eq!(Some("title"), attributes.get("class"));
+    }
+
+    #[test]
+    fn should_iterate_over_attributes_as_slices_of_the_html_code() {
+        let html = " id='foo' hidden class=\"bar baz\"";
+        let attributes: Vec<(&str, Option<&str>)> = AttributeIterator::new(html).collect();
+        assert_eq!(
+            vec![
+                ("id", Some("foo")),
+                ("hidden", None),
+                ("class", Some("bar baz"))
+            ],
+            attributes
+        );
+    }
+
+    #[test]
+    fn should_read_attribute_names_with_any_character() {
+        let html = " data_x='1' xlink:href=\"#a\" @click='go()' :prop='p' v-on:click='f'";
+        let attributes: Vec<(&str, Option<&str>)> = AttributeIterator::new(html).collect();
+        assert_eq!(
+            vec![
+                ("data_x", Some("1")),
+                ("xlink:href", Some("#a")),
+                ("@click", Some("go()")),
+                (":prop", Some("p")),
+                ("v-on:click", Some("f"))
+            ],
+            attributes
+        );
+    }
+
+    #[test]
+    fn should_read_unquoted_attribute_values() {
+        let html = " width=100 height = 20 alt=a'b href=foo/";
+        let attributes: Vec<(&str, Option<&str>)> = AttributeIterator::new(html).collect();
+        assert_eq!(
+            vec![
+                ("width", Some("100")),
+                ("height", Some("20")),
+                ("alt", Some("a'b")),
+                ("href", Some("foo/"))
+            ],
+            attributes
+        );
+    }
+
+    #[test]
+    fn should_keep_the_other_quote_inside_a_quoted_value() {
+        let html = " title=\"it's\" alt='say \"hi\"'";
+        let attributes: Vec<(&str, Option<&str>)> = AttributeIterator::new(html).collect();
+        assert_eq!(
+            vec![("title", Some("it's")), ("alt", Some("say \"hi\""))],
+            attributes
+        );
+    }
+
+    #[test]
+    fn should_read_attributes_not_separated_by_white_spaces() {
+        let html = " a='1'b=\"2\"/c";
+        let attributes: Vec<(&str, Option<&str>)> = AttributeIterator::new(html).collect();
+        assert_eq!(
+            vec![("a", Some("1")), ("b", Some("2")), ("c", None)],
+            attributes
+        );
+    }
+
+    #[test]
+    fn should_read_an_empty_value_at_last_position_of_the_html_code() {
+        let attributes: Vec<(&str, Option<&str>)> = AttributeIterator::new(" href=").collect();
+        assert_eq!(vec![("href", Some(""))], attributes);
+    }
+
+    #[test]
+    fn should_keep_the_first_value_of_a_repeated_attribute() {
+        let html = " class='foo' id=bar class='baz'";
+        let mut parser = TagParser::new();
+        let attributes = parser.parse_attributes(html, 0);
+        assert_eq!(Some("foo"), attributes.get("class"));
+        assert_eq!(Some("bar"), attributes.get("id"));
+    }
+
+    #[test]
+    fn should_read_the_position_of_each_attribute() {
+        let html = " id=\"foo\" width=100 hidden";
+        let mut parser = TagParser::new();
+        let attributes = parser.parse_attributes(html, 4);
+        let attributes: Vec<&Attribute> = attributes.iter().collect();
+
+        assert_eq!(5..7, attributes[0].name_span);
+        assert_eq!(Some(9..12), attributes[0].value_span);
+        assert_eq!(AttributeQuote::Double, attributes[0].quote);
+
+        assert_eq!(14..19, attributes[1].name_span);
+        assert_eq!(Some(20..23), attributes[1].value_span);
+        assert_eq!(AttributeQuote::None, attributes[1].quote);
+
+        assert_eq!(24..30, attributes[2].name_span);
+        assert_eq!(None, attributes[2].value_span);
     }
 }
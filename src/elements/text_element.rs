@@ -1,13 +1,13 @@
 use crate::elements::Element;
 
 #[derive(PartialEq, Debug)]
-pub struct TextElement {
-    pub content: String,
+pub struct TextElement<'a> {
+    pub content: &'a str,
     pub length: usize,
 }
 
-impl Element<TextElement> for TextElement {
-    fn extract(html: &str) -> Option<TextElement> {
+impl<'a> Element<'a, TextElement<'a>> for TextElement<'a> {
+    fn extract(html: &'a str) -> Option<TextElement<'a>> {
         if is_text_element(html) {
             let (content, length) = extract_text_element(html);
             Some(TextElement { content, length })
@@ -17,20 +17,41 @@ impl Element<TextElement> for TextElement {
     }
 }
 
-fn extract_text_element(html: &str) -> (String, usize) {
-    let end = html.find('<').unwrap_or_else(|| html.len());
-    let text = html.get(..end).unwrap();
-    (text.to_string(), end)
+fn extract_text_element(html: &str) -> (&str, usize) {
+    let end = html.find('<').unwrap_or(html.len());
+    (&html[..end], end)
 }
 
 fn is_text_element(html: &str) -> bool {
-    if !html.is_empty() {
-        let first_char = html.get(0..1);
-        match first_char {
-            Some(char_str) => char_str != "<",
-            None => false,
-        }
-    } else {
-        false
+    // the first character may be longer than 1 byte, like `é`
+    !html.is_empty() && !html.starts_with('<')
+}
+
+#[cfg(test)]
+mod test_text_element {
+    use super::*;
+
+    #[test]
+    fn should_extract_a_text_until_the_next_tag() {
+        assert_eq!(
+            Some(TextElement {
+                content: "hello ",
+                length: 6
+            }),
+            TextElement::extract("hello <b>world</b>")
+        );
+        assert_eq!(None, TextElement::extract("<b>world</b>"));
+        assert_eq!(None, TextElement::extract(""));
+    }
+
+    #[test]
+    fn should_extract_a_text_starting_with_a_non_ascii_character() {
+        assert_eq!(
+            Some(TextElement {
+                content: "€ 61,90",
+                length: 9
+            }),
+            TextElement::extract("€ 61,90</td>")
+        );
     }
 }
@@ -1,33 +1,37 @@
-pub fn extract_element_like(html: &str, start_str: &str, end_str: &str) -> (String, usize) {
-    let start = start_str.len();
-
-    let end = html.find(end_str).unwrap();
+use std::borrow::Cow;
 
-    let tag_content = html.get(start..end).unwrap();
+/// Extract the content of an element on a single line : line breaks inside the element are removed.
+pub fn extract_element_like<'a>(
+    html: &'a str,
+    start_str: &str,
+    end_str: &str,
+) -> Option<(Cow<'a, str>, usize)> {
+    let (tag_content, length) = extract_multiline_element_like(html, start_str, end_str)?;
 
-    let name: String = tag_content
-        .replace("\n\r", "")
-        .replace("\n", "")
-        .replace("\r", "");
+    let name = if tag_content.contains(&['\n', '\r'][..]) {
+        Cow::Owned(tag_content.replace(['\n', '\r'], ""))
+    } else {
+        Cow::Borrowed(tag_content)
+    };
 
-    let length = name.len() + start + end_str.len();
-    (name, length)
+    Some((name, length))
 }
-pub fn extract_multiline_element_like(
-    html: &str,
+/// Extract the content of an element between `start_str` and `end_str`. It returns `None` if the
+/// element does not end.
+pub fn extract_multiline_element_like<'a>(
+    html: &'a str,
     start_str: &str,
     end_str: &str,
-) -> (String, usize) {
+) -> Option<(&'a str, usize)> {
     let start = start_str.len();
 
-    let end = html.find(end_str).unwrap();
+    // the end is searched after the start, so `<!-->` does not end a comment
+    let end = start + html.get(start..)?.find(end_str)?;
 
-    let tag_content = html.get(start..end).unwrap();
+    let tag_content = html.get(start..end)?;
 
-    let name: String = tag_content.to_string();
-
-    let length = name.len() + start + end_str.len();
-    (name, length)
+    let length = end + end_str.len();
+    Some((tag_content, length))
 }
 
 #[cfg(test)]
@@ -40,7 +44,7 @@ mod test_utils {
         let content = "</div>";
         let start_str = "</";
         let end_str = ">";
-        let (name, length) = extract_element_like(content, start_str, end_str);
+        let (name, length) = extract_element_like(content, start_str, end_str).unwrap();
         assert_eq!("div".to_string(), name);
         assert_eq!(6, length);
     }
@@ -50,7 +54,7 @@ mod test_utils {
         let html = r#"<!--Hello
         World-->"#;
 
-        let (content, length) = extract_multiline_element_like(html, "<!--", "-->");
+        let (content, length) = extract_multiline_element_like(html, "<!--", "-->").unwrap();
 
         let expected_content = r#"Hello
         World"#
@@ -58,4 +62,11 @@ mod test_utils {
         assert_eq!(expected_content, content);
         assert_eq!(26, length);
     }
+
+    #[test]
+    fn should_not_extract_an_element_without_end() {
+        assert_eq!(None, extract_element_like("</div", "</", ">"));
+        assert_eq!(None, extract_multiline_element_like("<!-->", "<!--", "-->"));
+        assert_eq!(None, extract_multiline_element_like("<!-", "<!--", "-->"));
+    }
 }
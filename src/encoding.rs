@@ -0,0 +1,275 @@
+//! Detection and decoding of the character encoding of an HTML document, like a browser does :
+//! the byte order mark, then the `<meta>` charset declaration, then a guess from the content.
+
+use std::borrow::Cow;
+
+use crate::character_reference::windows_1252_c1_char;
+use crate::tag_iterator::{TagIterator, Token};
+use crate::utf8_decoder::{decode_utf8, InvalidUtf8};
+
+/// length of the beginning of the HTML document where the `<meta>` charset is searched
+const PRESCAN_LENGTH: usize = 1024;
+
+/// Character encoding of an HTML document
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    Utf8,
+    Utf16Le,
+    Utf16Be,
+    /// Also used for the documents declared in ISO-8859-1 or in ASCII, like browsers do
+    Windows1252,
+    Iso8859_15,
+}
+
+/// How the encoding of an HTML document was found
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EncodingSource {
+    /// The document starts with a byte order mark
+    ByteOrderMark,
+    /// The document declares its encoding with `<meta charset>` or with
+    /// `<meta http-equiv="Content-Type">`
+    MetaCharset,
+    /// The encoding is not declared : it is UTF-8 if the document is valid UTF-8, and
+    /// Windows-1252 otherwise
+    Guessed,
+}
+
+/// HTML document decoded into UTF-8
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecodedHtml<'a> {
+    pub html: Cow<'a, str>,
+    pub encoding: Encoding,
+    pub source: EncodingSource,
+}
+
+impl Encoding {
+    /// Encoding of a charset label, like `latin1`, case-insensitive. Unsupported encodings are
+    /// `None`.
+    pub fn for_label(label: &str) -> Option<Encoding> {
+        let encoding = match label.trim().to_ascii_lowercase().as_str() {
+            "utf-8" | "utf8" | "unicode-1-1-utf-8" => Encoding::Utf8,
+            "utf-16" | "utf-16le" => Encoding::Utf16Le,
+            "utf-16be" => Encoding::Utf16Be,
+            "windows-1252" | "cp1252" | "x-cp1252" | "iso-8859-1" | "iso8859-1" | "iso_8859-1"
+            | "iso88591" | "latin1" | "l1" | "ascii" | "us-ascii" | "cp819" | "ibm819"
+            | "csisolatin1" => Encoding::Windows1252,
+            "iso-8859-15" | "iso8859-15" | "iso_8859-15" | "iso885915" | "latin-9" | "l9"
+            | "csisolatin9" => Encoding::Iso8859_15,
+            _ => return None,
+        };
+        Some(encoding)
+    }
+
+    /// Name of the encoding, like `windows-1252`
+    pub fn name(&self) -> &'static str {
+        match self {
+            Encoding::Utf8 => "UTF-8",
+            Encoding::Utf16Le => "UTF-16LE",
+            Encoding::Utf16Be => "UTF-16BE",
+            Encoding::Windows1252 => "windows-1252",
+            Encoding::Iso8859_15 => "ISO-8859-15",
+        }
+    }
+
+    /// Decode `bytes` into UTF-8. The invalid sequences are replaced with `U+FFFD`.
+    pub fn decode<'a>(&self, bytes: &'a [u8]) -> Cow<'a, str> {
+        match self {
+            Encoding::Utf8 => decode_utf8(bytes, InvalidUtf8::Replace).unwrap_or_default(),
+            Encoding::Utf16Le => Cow::Owned(decode_utf16(bytes, u16::from_le_bytes)),
+            Encoding::Utf16Be => Cow::Owned(decode_utf16(bytes, u16::from_be_bytes)),
+            // ASCII is the same in all these encodings
+            _ if bytes.is_ascii() => String::from_utf8_lossy(bytes),
+            Encoding::Windows1252 => {
+                Cow::Owned(bytes.iter().map(|b| windows_1252_char(*b)).collect())
+            }
+            Encoding::Iso8859_15 => {
+                Cow::Owned(bytes.iter().map(|b| iso_8859_15_char(*b)).collect())
+            }
+        }
+    }
+}
+
+/// Detect the encoding of an HTML document and decode it into UTF-8. The byte order mark is
+/// removed, so the positions of the elements are positions into the decoded HTML document.
+pub fn decode_html(bytes: &[u8]) -> DecodedHtml<'_> {
+    let (encoding, source, bom_length) = detect_encoding(bytes);
+    DecodedHtml {
+        html: encoding.decode(&bytes[bom_length..]),
+        encoding,
+        source,
+    }
+}
+
+/// Encoding of an HTML document, with the length of its byte order mark
+fn detect_encoding(bytes: &[u8]) -> (Encoding, EncodingSource, usize) {
+    if let Some((encoding, bom_length)) = byte_order_mark(bytes) {
+        (encoding, EncodingSource::ByteOrderMark, bom_length)
+    } else if let Some(encoding) = meta_charset(&bytes[..bytes.len().min(PRESCAN_LENGTH)]) {
+        (encoding, EncodingSource::MetaCharset, 0)
+    } else if std::str::from_utf8(bytes).is_ok() {
+        (Encoding::Utf8, EncodingSource::Guessed, 0)
+    } else {
+        (Encoding::Windows1252, EncodingSource::Guessed, 0)
+    }
+}
+
+/// Encoding given by the byte order mark at the beginning of `bytes`, with its length
+fn byte_order_mark(bytes: &[u8]) -> Option<(Encoding, usize)> {
+    if bytes.starts_with(b"\xEF\xBB\xBF") {
+        Some((Encoding::Utf8, 3))
+    } else if bytes.starts_with(b"\xFF\xFE") {
+        Some((Encoding::Utf16Le, 2))
+    } else if bytes.starts_with(b"\xFE\xFF") {
+        Some((Encoding::Utf16Be, 2))
+    } else {
+        None
+    }
+}
+
+/// Encoding declared by the 1st `<meta>` tag with a supported charset. A document declared in
+/// UTF-16 is read in UTF-8 : being readable as ASCII, it is not UTF-16.
+fn meta_charset(bytes: &[u8]) -> Option<Encoding> {
+    let html = String::from_utf8_lossy(bytes);
+    let encoding = TagIterator::new(&html)
+        .tokens()
+        .find_map(|token| match token {
+            Token::Start(tag, _, _) if tag.name.eq_ignore_ascii_case("meta") => {
+                let attribute = |name: &str| {
+                    tag.attributes()
+                        .find(|(attribute_name, _)| attribute_name.eq_ignore_ascii_case(name))
+                        .map(|(_, value)| value.unwrap_or_default())
+                };
+                match (
+                    attribute("charset"),
+                    attribute("http-equiv"),
+                    attribute("content"),
+                ) {
+                    (Some(charset), _, _) => Encoding::for_label(charset),
+                    (None, Some(http_equiv), Some(content))
+                        if http_equiv.eq_ignore_ascii_case("content-type") =>
+                    {
+                        content_type_charset(content).and_then(Encoding::for_label)
+                    }
+                    _ => None,
+                }
+            }
+            _ => None,
+        })?;
+    match encoding {
+        Encoding::Utf16Le | Encoding::Utf16Be => Some(Encoding::Utf8),
+        _ => Some(encoding),
+    }
+}
+
+/// Charset of a content type, like `utf-8` in `text/html; charset="utf-8"`
+fn content_type_charset(content_type: &str) -> Option<&str> {
+    let position = content_type.to_ascii_lowercase().find("charset")?;
+    let value = content_type[position + "charset".len()..]
+        .trim_start()
+        .strip_prefix('=')?
+        .trim_start();
+    let value = value.trim_start_matches(['"', '\'']);
+    value
+        .split(|c: char| c == ';' || c == '"' || c == '\'' || c.is_ascii_whitespace())
+        .next()
+        .filter(|charset| !charset.is_empty())
+}
+
+/// Decode UTF-16 code units read with `from_bytes`. An odd last byte is an invalid sequence.
+fn decode_utf16(bytes: &[u8], from_bytes: fn([u8; 2]) -> u16) -> String {
+    let code_units = bytes
+        .chunks_exact(2)
+        .map(|pair| from_bytes([pair[0], pair[1]]));
+    let mut html: String = char::decode_utf16(code_units)
+        .map(|c| c.unwrap_or(char::REPLACEMENT_CHARACTER))
+        .collect();
+    if bytes.len() % 2 == 1 {
+        html.push(char::REPLACEMENT_CHARACTER);
+    }
+    html
+}
+
+/// Character of a Windows-1252 byte. The 5 bytes undefined in Windows-1252 are read like in
+/// ISO-8859-1.
+pub(crate) fn windows_1252_char(byte: u8) -> char {
+    windows_1252_c1_char(u32::from(byte)).unwrap_or(char::from(byte))
+}
+
+/// Character of an ISO-8859-15 byte : it is ISO-8859-1 where 8 characters are replaced, like `€`
+fn iso_8859_15_char(byte: u8) -> char {
+    match byte {
+        0xA4 => '\u{20AC}',
+        0xA6 => '\u{0160}',
+        0xA8 => '\u{0161}',
+        0xB4 => '\u{017D}',
+        0xB8 => '\u{017E}',
+        0xBC => '\u{0152}',
+        0xBD => '\u{0153}',
+        0xBE => '\u{0178}',
+        _ => char::from(byte),
+    }
+}
+
+#[cfg(test)]
+mod test_encoding {
+    use super::*;
+
+    #[test]
+    fn should_detect_the_encoding_from_the_byte_order_mark() {
+        let decoded = decode_html(b"\xEF\xBB\xBF<p>caf\xC3\xA9</p>");
+        assert_eq!("<p>café</p>", decoded.html);
+        assert_eq!(Encoding::Utf8, decoded.encoding);
+        assert_eq!(EncodingSource::ByteOrderMark, decoded.source);
+
+        let decoded = decode_html(b"\xFE\xFF\x00<\x00p\x00>\x00\xE9");
+        assert_eq!("<p>é", decoded.html);
+        assert_eq!(Encoding::Utf16Be, decoded.encoding);
+    }
+
+    #[test]
+    fn should_detect_the_encoding_from_the_meta_charset() {
+        let decoded = decode_html(b"<html><head><META CharSet='ISO-8859-15'><p>\xA4 \xE9</p>");
+        assert_eq!(
+            "<html><head><META CharSet='ISO-8859-15'><p>€ é</p>",
+            decoded.html
+        );
+        assert_eq!(Encoding::Iso8859_15, decoded.encoding);
+        assert_eq!(EncodingSource::MetaCharset, decoded.source);
+
+        let decoded = decode_html(
+            b"<meta http-equiv=\"Content-Type\" content=\"text/html; charset=iso-8859-1\">\x80",
+        );
+        assert_eq!(Encoding::Windows1252, decoded.encoding);
+        assert!(decoded.html.ends_with('€'));
+
+        // the declaration in a comment is ignored
+        let decoded = decode_html(b"<!-- <meta charset=latin1> --><meta charset=utf-16>\xE9");
+        assert_eq!(Encoding::Utf8, decoded.encoding);
+        assert!(decoded.html.ends_with('\u{FFFD}'));
+    }
+
+    #[test]
+    fn should_guess_the_encoding_of_an_undeclared_document() {
+        let decoded = decode_html("<p>café</p>".as_bytes());
+        assert!(matches!(decoded.html, Cow::Borrowed("<p>café</p>")));
+        assert_eq!(Encoding::Utf8, decoded.encoding);
+        assert_eq!(EncodingSource::Guessed, decoded.source);
+
+        let decoded = decode_html(b"<p>caf\xE9 \x93ok\x94</p>");
+        assert_eq!("<p>café “ok”</p>", decoded.html);
+        assert_eq!(Encoding::Windows1252, decoded.encoding);
+    }
+
+    #[test]
+    fn should_read_the_charset_of_a_content_type() {
+        assert_eq!(
+            Some("utf-8"),
+            content_type_charset("text/html; Charset = \"utf-8\"")
+        );
+        assert_eq!(
+            Some("latin1"),
+            content_type_charset("text/html;charset=latin1;foo")
+        );
+        assert_eq!(None, content_type_charset("text/html"));
+    }
+}
@@ -0,0 +1,369 @@
+use std::io;
+
+use crate::elements::start_element::TagEndSearch;
+use crate::parse_error::{LineColumn, ParseError};
+use crate::parser_options::ParserOptions;
+use crate::tag_iterator::{
+    extract_next_token, is_complete_element, is_complete_raw_text, is_unterminated_markup,
+    parse_error_kind, raw_text_element, read_element, recover_token, Elements, Token,
+};
+use crate::tag_path::OpenElements;
+use crate::utf8_decoder::{InvalidUtf8, Utf8Decoder};
+
+/// HTML code received chunk by chunk and not read yet. Elements are read from the beginning of
+/// the buffer only when they are complete, so an element split between 2 chunks is read once
+/// the 2nd chunk is received.
+///
+/// The HTML code already read is removed from the buffer only when it is at least half of the
+/// buffer, so each character is moved a constant number of times.
+#[derive(Debug, Default)]
+pub struct HtmlBuffer {
+    /// HTML code received, the HTML code not read yet starts at `start`
+    html: String,
+    /// position into `html` of the last element read, followed by the HTML code not read yet
+    start: usize,
+    /// decoder of the chunks of UTF-8 encoded HTML code
+    decoder: Utf8Decoder,
+    /// position of `start` into the whole HTML document
+    reading_position: usize,
+    /// line and column of the beginning of `html` into the whole HTML document
+    html_line_column: LineColumn,
+    /// length of the last element read
+    last_element_length: usize,
+    /// length of the HTML code not read yet that is known to not contain a complete element
+    checked_length: usize,
+    /// search of the end of the tag at the beginning of the HTML code not read yet
+    tag_end_search: TagEndSearch,
+    /// true once the HTML code not read yet is known to start with a complete element
+    has_complete_element: bool,
+    /// name of the element whose content is read as text, like `script`
+    raw_text_element: Option<&'static str>,
+    /// options like the strict mode, where the HTML code that can not be read as an element is an
+    /// error, or the XML mode
+    options: ParserOptions,
+    /// position into the whole HTML document after which there is no `>`, once the last chunk
+    /// is received : the elements starting with `<` after it can not end
+    no_markup_end_from: Option<usize>,
+    /// elements open around the next element returned by `next_element`
+    open_elements: OpenElements,
+}
+
+impl HtmlBuffer {
+    pub fn new() -> Self {
+        HtmlBuffer::default()
+    }
+
+    /// Read the elements with `options`, like the decoding of the character references : in
+    /// strict mode, the HTML code that can not be read as an element is an error instead of being
+    /// read like a browser does, and in XML mode the CDATA sections and the processing
+    /// instructions are read too. The tokens are never decoded : they borrow the HTML code.
+    pub fn options(mut self, options: ParserOptions) -> Self {
+        self.options = options;
+        self.open_elements = self.open_elements.options(options);
+        self
+    }
+
+    /// Read the bytes that are not valid UTF-8 with the given policy, instead of failing
+    pub fn invalid_utf8(mut self, invalid_utf8: InvalidUtf8) -> Self {
+        self.decoder = Utf8Decoder::new(invalid_utf8);
+        self
+    }
+
+    /// Add a chunk of HTML code at the end of the buffer
+    pub fn push_str(&mut self, html: &str) {
+        if self.start > 0 && self.start >= self.html.len() / 2 {
+            self.html_line_column = self.html_line_column.advance(&self.html[..self.start]);
+            self.html.drain(..self.start);
+            self.start = 0;
+        }
+        self.html.push_str(html);
+    }
+
+    /// Add a chunk of UTF-8 encoded HTML code at the end of the buffer. A character split between
+    /// 2 chunks is kept until the next chunk is received.
+    pub fn push_bytes(&mut self, bytes: &[u8]) -> io::Result<()> {
+        let mut html = String::with_capacity(bytes.len());
+        self.decoder.decode(bytes, &mut html)?;
+        self.push_str(&html);
+        Ok(())
+    }
+
+    /// Add the end of the UTF-8 encoded HTML code : a character not completed by the last chunk
+    /// is an invalid UTF-8 sequence. It must be called once all the chunks are received.
+    pub fn finish_bytes(&mut self) -> io::Result<()> {
+        let mut html = String::new();
+        self.decoder.finish(&mut html)?;
+        self.push_str(&html);
+        Ok(())
+    }
+
+    /// Read the next element of the buffer. It returns `None` when the buffer does not contain a
+    /// complete element, unless `is_last_chunk` is true : then the end of the buffer is the end of
+    /// the HTML document. In strict mode, it fails if the HTML code can not be read as an element.
+    ///
+    /// The elements are read with the options like the tag iterator does : the elements skipped
+    /// by the options are not returned, the character references are decoded and the names of
+    /// the HTML elements are lowercased if the options ask for it.
+    pub fn next_element(&mut self, is_last_chunk: bool) -> Result<Option<Elements>, ParseError> {
+        loop {
+            let raw_text_element = self.raw_text_element;
+            let options = self.options;
+            let (token, html) = match self.next_token_html(is_last_chunk)? {
+                Some(token) => token,
+                None => return Ok(None),
+            };
+            if let Some(mut element) = read_element(token, html, raw_text_element, options) {
+                self.open_elements.read(&mut element);
+                return Ok(Some(element));
+            }
+        }
+    }
+
+    /// Returns true if the buffer starts with a complete element, that the next call of
+    /// `next_token_html` reads. The last element read is removed from the HTML code not read yet.
+    pub fn has_next_element(&mut self, is_last_chunk: bool) -> bool {
+        self.consume_last_element();
+        if self.has_complete_element {
+            return true;
+        }
+        let html = &self.html[self.start..];
+        // the end of the buffer is the end of the last element, so its end is not searched
+        let is_complete = is_last_chunk
+            || match self.raw_text_element {
+                Some(element_name) => is_complete_raw_text(html, element_name, self.checked_length),
+                None => is_complete_element(
+                    html,
+                    self.checked_length,
+                    &mut self.tag_end_search,
+                    self.options.xml,
+                ),
+            };
+        if html.is_empty() || !is_complete {
+            self.checked_length = html.len();
+            return false;
+        }
+        self.has_complete_element = true;
+        true
+    }
+
+    /// Read the next token of the buffer with its HTML code, like `next_element` but borrowing
+    /// the HTML code of the buffer
+    pub fn next_token_html(
+        &mut self,
+        is_last_chunk: bool,
+    ) -> Result<Option<(Token<'_>, &str)>, ParseError> {
+        if !self.has_next_element(is_last_chunk) {
+            return Ok(None);
+        }
+        let html = &self.html[self.start..];
+        let no_markup_end_from = self.no_markup_end_from.filter(|_| is_last_chunk);
+        let next_token = if is_unterminated_markup(
+            html,
+            self.reading_position,
+            no_markup_end_from,
+            self.raw_text_element,
+        ) {
+            None
+        } else {
+            extract_next_token(
+                html,
+                self.reading_position,
+                self.raw_text_element,
+                self.options.xml,
+            )
+        };
+        let (token, length) = match next_token {
+            Some(token) => token,
+            None if self.options.strict => return Err(self.parse_error()),
+            None => {
+                if is_last_chunk && no_markup_end_from.is_none() && !html.contains('>') {
+                    self.no_markup_end_from = Some(self.reading_position);
+                }
+                recover_token(html, self.reading_position)
+            }
+        };
+        self.raw_text_element = raw_text_element(&token).filter(|_| !self.options.xml);
+        self.last_element_length = length;
+        self.checked_length = 0;
+        self.tag_end_search = TagEndSearch::default();
+        self.has_complete_element = false;
+        Ok(Some((token, &html[..length])))
+    }
+
+    /// Error of the HTML code not read yet, that can not be read as an element
+    fn parse_error(&self) -> ParseError {
+        let line_column = self.html_line_column.advance(&self.html[..self.start]);
+        let kind = parse_error_kind(&self.html[self.start..]);
+        ParseError::after(kind, self.reading_position, line_column)
+    }
+
+    /// Move the beginning of the HTML code not read yet after the last element read
+    fn consume_last_element(&mut self) {
+        self.start += self.last_element_length;
+        self.reading_position += self.last_element_length;
+        self.last_element_length = 0;
+    }
+}
+
+#[cfg(test)]
+mod test_html_buffer {
+    use super::*;
+    use crate::parse_error::ParseErrorKind;
+    use crate::tag_iterator::TagIterator;
+
+    #[test]
+    fn should_wait_for_the_end_of_a_tag_split_between_2_chunks() {
+        let mut buffer = HtmlBuffer::new();
+        buffer.push_bytes(b"<div cla").unwrap();
+        assert_eq!(Ok(None), buffer.next_element(false));
+
+        buffer.push_bytes(b"ss='foo'>hello").unwrap();
+        let token = buffer.next_token_html(false);
+        assert!(matches!(
+            token,
+            Ok(Some((Token::Start(_, 0, 17), "<div class='foo'>")))
+        ));
+        // the text may continue in the next chunk
+        assert_eq!(Ok(None), buffer.next_element(false));
+        assert_eq!(
+            Ok(Some(Elements::Text("hello".to_string(), 17, 22))),
+            buffer.next_element(true)
+        );
+    }
+
+    #[test]
+    fn should_wait_for_the_end_of_a_tag_given_a_quoted_greater_than_sign() {
+        let mut buffer = HtmlBuffer::new();
+        buffer.push_bytes(b"<a title='1 > 0").unwrap();
+        assert_eq!(Ok(None), buffer.next_element(false));
+
+        buffer.push_bytes(b"'>").unwrap();
+        let token = buffer.next_token_html(false);
+        assert!(matches!(
+            token,
+            Ok(Some((Token::Start(_, 0, 17), "<a title='1 > 0'>")))
+        ));
+    }
+
+    #[test]
+    fn should_keep_an_utf8_character_split_between_2_chunks() {
+        let html = "<p>a€</p>".as_bytes();
+        let mut buffer = HtmlBuffer::new();
+        buffer.push_bytes(&html[..5]).unwrap();
+        assert!(buffer.decoder.has_incomplete_char());
+        buffer.push_bytes(&html[5..]).unwrap();
+        assert!(!buffer.decoder.has_incomplete_char());
+
+        assert!(matches!(
+            buffer.next_element(false),
+            Ok(Some(Elements::Start(_, 0, 3)))
+        ));
+        assert_eq!(
+            Ok(Some(Elements::Text("a€".to_string(), 3, 7))),
+            buffer.next_element(false)
+        );
+        assert_eq!(
+            Ok(Some(Elements::End("p".to_string(), 7, 11))),
+            buffer.next_element(false)
+        );
+    }
+
+    #[test]
+    fn should_give_the_position_of_an_element_without_end() {
+        let mut buffer = HtmlBuffer::new().options(ParserOptions::new().strict(true));
+        buffer.push_str("<p>\nfoo</p>\n<");
+        buffer.push_str("!-- bar");
+        while let Ok(Some(_)) = buffer.next_element(false) {}
+        // the comment may end in the next chunk
+        assert_eq!(Ok(None), buffer.next_element(false));
+
+        let error = buffer.next_element(true).unwrap_err();
+        assert_eq!(ParseErrorKind::UnterminatedComment, error.kind);
+        assert_eq!((12, 3, 1), (error.offset, error.line, error.column));
+    }
+
+    #[test]
+    fn should_wait_for_the_end_of_a_cdata_section_in_xml() {
+        let xml = "<a><![CDATA[1 > 0]]><?pi a > b?></a>";
+        let expected_elements: Vec<Elements> = TagIterator::new(xml)
+            .options(ParserOptions::new().xml(true))
+            .collect();
+
+        for chunk_size in 1..xml.len() {
+            let mut buffer = HtmlBuffer::new().options(ParserOptions::new().xml(true));
+            let mut elements = vec![];
+            for chunk in xml.as_bytes().chunks(chunk_size) {
+                buffer.push_bytes(chunk).unwrap();
+                while let Ok(Some(element)) = buffer.next_element(false) {
+                    elements.push(element);
+                }
+            }
+            while let Ok(Some(element)) = buffer.next_element(true) {
+                elements.push(element);
+            }
+            assert_eq!(expected_elements, elements, "chunk size {}", chunk_size);
+        }
+        assert_eq!(
+            Elements::Text("1 > 0".to_string(), 3, 20),
+            expected_elements[1]
+        );
+    }
+
+    #[test]
+    fn should_read_the_elements_with_the_options_like_the_tag_iterator() {
+        let html = "<p title='&lt;b&gt;'>caf&eacute;</p>\n<script>a &lt; b</script>";
+        let options = ParserOptions::new()
+            .decode_character_references(true)
+            .skip_whitespace_texts(true);
+        let expected_elements: Vec<Elements> = TagIterator::new(html).options(options).collect();
+
+        for chunk_size in 1..html.len() {
+            let mut buffer = HtmlBuffer::new().options(options);
+            let mut elements = vec![];
+            for chunk in html.as_bytes().chunks(chunk_size) {
+                buffer.push_bytes(chunk).unwrap();
+                while let Ok(Some(element)) = buffer.next_element(false) {
+                    elements.push(element);
+                }
+            }
+            while let Ok(Some(element)) = buffer.next_element(true) {
+                elements.push(element);
+            }
+            assert_eq!(expected_elements, elements, "chunk size {}", chunk_size);
+        }
+        assert_eq!(
+            Elements::Text("café".to_string(), 21, 32),
+            expected_elements[1]
+        );
+        assert_eq!(
+            Elements::Text("a &lt; b".to_string(), 45, 53),
+            expected_elements[4]
+        );
+    }
+
+    #[test]
+    fn should_reject_invalid_utf8() {
+        let mut buffer = HtmlBuffer::new();
+        let result = buffer.push_bytes(&[b'<', 0xff, b'>']);
+        assert_eq!(io::ErrorKind::InvalidData, result.unwrap_err().kind());
+    }
+
+    #[test]
+    fn should_replace_invalid_utf8_split_between_2_chunks() {
+        let mut buffer = HtmlBuffer::new().invalid_utf8(InvalidUtf8::Replace);
+        buffer.push_bytes(b"<p>\xe2").unwrap();
+        buffer.push_bytes(b"\x82</p><b>\xe2").unwrap();
+        buffer.finish_bytes().unwrap();
+
+        let elements: Vec<Elements> = std::iter::from_fn(|| buffer.next_element(true).unwrap())
+            .filter(|element| matches!(element, Elements::Text(..)))
+            .collect();
+        assert_eq!(
+            vec![
+                Elements::Text("\u{FFFD}".to_string(), 3, 6),
+                Elements::Text("\u{FFFD}".to_string(), 13, 16)
+            ],
+            elements
+        );
+    }
+}
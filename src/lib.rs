@@ -1,43 +1,109 @@
-//! This is a parser that read an HTML content in a stream fashion way. While it is reading 
-//! the HTML content it tries to match each CSS requests.
+//! This is a parser that reads an HTML content in a stream fashion way. While it is reading the
+//! HTML content, it tries to match each CSS request, so the HTML content is only read once and all
+//! the requests must be given before the reading, within an _array like structure_.
 //!
-//! A CSS request is always made with a array like structure where each element should match a tag 
-//! in the DOM tree. The last element of the "array" is the last element that must be counted or 
-//! returned. CSS request specification are not implemented fully. For example, all relevant parent
-//! (for the matching) must be written.
+//! A CSS request is always made with an array like structure where each element should match a
+//! tag in the DOM tree. The last element of the "array" is the element that is counted or whose
+//! content is returned. CSS requests are not implemented fully : for example, all the relevant
+//! parents (for the matching) must be written. A CSS request can be
+//! - tag names only, within a string slice like `"div p"` (see `by_name`)
+//! - predicates on the tag path items, like `|item| item.tag.name == "p"` (see `with_matcher`)
+//! - a CSS selector built with macros, like `css_selector!(td[valign="top"])` (see `by_tag_path`)
 //!
-//! A CSS request can be 
-//! - only tag name within a strning slice `"div p"`
-//! - a predicate **TODO say more**
-//! - a CSS selector built with macros
+//! Each module counts the matching elements (`count`) or returns the content of the first one
+//! (`find_first`). The HTML content is either
+//! - a string slice
+//! - UTF-8 encoded bytes (the `*_bytes` functions) that may contain invalid UTF-8, like in many
+//!   HTML emails : the invalid bytes are replaced with `U+FFFD` or read as Windows-1252
+//!   characters, depending on the `InvalidUtf8` policy, and the contents found are parts of the
+//!   decoded HTML content. When the encoding is not known, `decode_html` detects it from the byte
+//!   order mark or the `<meta>` charset declaration, like a browser does.
+//! - any `std::io::Read` (the `*_from_reader` functions), read chunk by chunk so the whole HTML
+//!   content is never kept in memory, or a `futures::io::AsyncRead` with the `async` feature
+//! - pieces fed to `by_tag_path::PushTagPathHtmlSelector` as they arrive, which returns the
+//!   matches as soon as they are known
 //!
-//! TODO : CSS request implemented
-//! 
-//! because the HTML content is only read once, we must provide before the reading all requests
-//! where we want a match. All the requests are given within an _array like structure_.
+//! The HTML content is read like a browser does : the void elements, like `<br>`, have no end
+//! tag, a start tag may close the elements whose end tag is optional, like a `<li>` closes an open
+//! `<li>`, and an end tag that matches no open element is ignored. The names of the HTML elements
+//! and of their attributes are lowercased, so `<TD VALIGN=top>` matches `td[valign="top"]`, while
+//! the names written are kept in `original_name`. The elements inside an inline `<svg>` or
+//! `<math>` are foreign content : they keep the case of their names, like `linearGradient`, and
+//! the CSS selectors can match their namespace, like `css_selector!(svg | rect)`. The HTML code
+//! that can not be read as an element, like `< 3`, `<!foo>` or an unterminated comment, is read
+//! as a text or as a comment.
+//!
+//! The `*_with_options` functions, the selectors and the `TagIterator` read the HTML content with
+//! `ParserOptions`, like
+//! - `decode_character_references(true)` to decode the character references, like `&eacute;`,
+//!   which are otherwise kept as is in the contents found
+//! - the white space policy and the maximum length of the contents found
+//! - `void_elements(false)` and `implied_end_tags(false)` to close the elements only with their
+//!   end tag, or when they are written like `<br/>`
+//! - `fold_case(false)` to keep the case of the names
+//! - `xml(true)` to read XML documents, like XHTML documents or RSS feeds : the names are
+//!   case-sensitive, only the elements written like `<div/>` have no end tag, no end tag is
+//!   implied, the CDATA sections are read as texts and the processing instructions, like
+//!   `<?xml version="1.0"?>`, are read as elements
+//! - `strict(true)` to fail with a `ParseError` giving the position of the HTML code that can not
+//!   be read as an element, like the `try_*` functions do
+//!
+//! The `tokenizer` module gives the elements read by the selectors, with their span and their
+//! depth, to build other tools on the same HTML reading.
 
 
 pub mod selectors;
+mod character_reference;
+mod encoding;
+pub use encoding::{decode_html, DecodedHtml, Encoding, EncodingSource};
+mod html_buffer;
+mod line_index;
+pub use line_index::LineIndex;
+mod parse_error;
+pub use parse_error::{LineColumn, ParseError, ParseErrorKind};
+mod parser_options;
+pub use parser_options::{ParserOptions, Whitespace};
+mod reader_tag_iterator;
 mod tag_iterator;
 mod tag_path;
+mod utf8_decoder;
+pub use utf8_decoder::{decode_utf8, InvalidUtf8};
 
 mod elements;
 
+pub mod tokenizer;
+
 /// this will search into html based on tag name only.
 // a query is a Vec<&str> where each element is a tag name to match.
 // the query does not have its length equals to the actual depth of the element.
 /// HTML code is not validated before. It has to be valid HTML
 pub mod by_name {
 
+    use super::parse_error::ParseError;
+    use super::parser_options::ParserOptions;
+    use super::reader_tag_iterator::ReaderTagIterator;
     use super::selectors::tag_name_html_selector::TagNameHtmlSelector;
     use super::selectors::HtmlSelectorCounter;
     use super::selectors::HtmlSelectorFindFirst;
+    use super::tag_iterator::TagIterator;
+    use super::utf8_decoder::{decode_utf8, InvalidUtf8};
+    use std::io::{self, Read};
 
     /// Count the matching of each element in the request Vector.
     /// - `html` is an HTML code
     /// - `css_requests` contains a list of CSS selectors. **One CSS Selector** is a list of tag name within a `Vec<&str>` struct.
     pub fn count(html: &str, css_requests: &[&str]) -> Vec<usize> {
-        let mut html_selector = TagNameHtmlSelector::default();
+        count_with_options(html, css_requests, ParserOptions::new())
+    }
+
+    /// Same as `count` but the HTML code is read with `options`, like the decoding of the character
+    /// references or the XML mode.
+    pub fn count_with_options(
+        html: &str,
+        css_requests: &[&str],
+        options: ParserOptions,
+    ) -> Vec<usize> {
+        let mut html_selector = TagNameHtmlSelector::default().options(options);
         html_selector.count(html, css_requests)
     }
 
@@ -46,16 +112,127 @@ pub mod by_name {
     /// - `html` is an HTML code
     /// - `css_requests` contains a list of CSS selectors. **One CSS Selector** is a list of tag name within a `Vec<&str>` struct.
     pub fn find_first(html: &str, css_requests: &[&str]) -> Vec<String> {
-        let mut html_selector = TagNameHtmlSelector::default();
+        find_first_with_options(html, css_requests, ParserOptions::new())
+    }
+
+    /// Same as `find_first` but the HTML code is read with `options`, like the decoding of the
+    /// character references or the XML mode.
+    pub fn find_first_with_options(
+        html: &str,
+        css_requests: &[&str],
+        options: ParserOptions,
+    ) -> Vec<String> {
+        let mut html_selector = TagNameHtmlSelector::default().options(options);
         html_selector.find_first(html, css_requests)
     }
+
+    /// Same as `count` but it fails on the HTML code that can not be read as an element.
+    pub fn try_count(html: &str, css_requests: &[&str]) -> Result<Vec<usize>, ParseError> {
+        try_count_with_options(html, css_requests, ParserOptions::new())
+    }
+
+    /// Same as `try_count` but the HTML code is read with `options`, in strict mode.
+    pub fn try_count_with_options(
+        html: &str,
+        css_requests: &[&str],
+        options: ParserOptions,
+    ) -> Result<Vec<usize>, ParseError> {
+        let options = options.strict(true);
+        let mut tag_iterator = TagIterator::new(html).options(options);
+        let mut html_selector = TagNameHtmlSelector::default().options(options);
+        let counts = html_selector.count_elements(&mut tag_iterator, css_requests);
+        tag_iterator.take_error().map_or(Ok(counts), Err)
+    }
+
+    /// Same as `find_first` but it fails on the HTML code that can not be read as an element.
+    pub fn try_find_first(html: &str, css_requests: &[&str]) -> Result<Vec<String>, ParseError> {
+        try_find_first_with_options(html, css_requests, ParserOptions::new())
+    }
+
+    /// Same as `try_find_first` but the HTML code is read with `options`, in strict mode.
+    pub fn try_find_first_with_options(
+        html: &str,
+        css_requests: &[&str],
+        options: ParserOptions,
+    ) -> Result<Vec<String>, ParseError> {
+        let options = options.strict(true);
+        let mut tag_iterator = TagIterator::new(html).options(options);
+        let mut html_selector = TagNameHtmlSelector::default().options(options);
+        let founds = html_selector.find_first_elements(&mut tag_iterator, css_requests);
+        tag_iterator.take_error().map_or(Ok(founds), Err)
+    }
+
+    /// Same as `count` but the HTML code is UTF-8 encoded. The bytes that are not valid UTF-8 are
+    /// read with the `invalid_utf8` policy, like `decode_utf8` does.
+    pub fn count_bytes(
+        html: &[u8],
+        css_requests: &[&str],
+        invalid_utf8: InvalidUtf8,
+    ) -> io::Result<Vec<usize>> {
+        Ok(count(&decode_utf8(html, invalid_utf8)?, css_requests))
+    }
+
+    /// Same as `find_first` but the HTML code is UTF-8 encoded. The bytes that are not valid UTF-8
+    /// are read with the `invalid_utf8` policy, like `decode_utf8` does.
+    pub fn find_first_bytes(
+        html: &[u8],
+        css_requests: &[&str],
+        invalid_utf8: InvalidUtf8,
+    ) -> io::Result<Vec<String>> {
+        Ok(find_first(&decode_utf8(html, invalid_utf8)?, css_requests))
+    }
+
+    /// Same as `count` but the HTML code is read chunk by chunk from `reader`.
+    pub fn count_from_reader<R: Read>(reader: R, css_requests: &[&str]) -> io::Result<Vec<usize>> {
+        count_from_reader_with_options(reader, css_requests, ParserOptions::new())
+    }
+
+    /// Same as `count_from_reader` but the HTML code is read with `options`, like the decoding of
+    /// the character references or the XML mode.
+    pub fn count_from_reader_with_options<R: Read>(
+        reader: R,
+        css_requests: &[&str],
+        options: ParserOptions,
+    ) -> io::Result<Vec<usize>> {
+        let mut tag_iterator = ReaderTagIterator::new(reader).options(options);
+        let mut html_selector = TagNameHtmlSelector::default().options(options);
+        let counts = html_selector.count_elements(&mut tag_iterator, css_requests);
+        tag_iterator.take_error().map_or(Ok(counts), Err)
+    }
+
+    /// Same as `find_first` but the HTML code is read chunk by chunk from `reader`.
+    pub fn find_first_from_reader<R: Read>(
+        reader: R,
+        css_requests: &[&str],
+    ) -> io::Result<Vec<String>> {
+        find_first_from_reader_with_options(reader, css_requests, ParserOptions::new())
+    }
+
+    /// Same as `find_first_from_reader` but the HTML code is read with `options`, like the decoding
+    /// of the character references or the XML mode.
+    pub fn find_first_from_reader_with_options<R: Read>(
+        reader: R,
+        css_requests: &[&str],
+        options: ParserOptions,
+    ) -> io::Result<Vec<String>> {
+        let mut tag_iterator = ReaderTagIterator::new(reader).options(options);
+        let mut html_selector = TagNameHtmlSelector::default().options(options);
+        let founds = html_selector.find_first_elements(&mut tag_iterator, css_requests);
+        tag_iterator.take_error().map_or(Ok(founds), Err)
+    }
 }
 
 pub mod with_matcher {
+    use super::parse_error::ParseError;
+    use super::parser_options::ParserOptions;
+    use super::reader_tag_iterator::ReaderTagIterator;
     use super::selectors::matcher_html_selector::MatcherHtmlSelector;
     use super::selectors::HtmlSelectorCounter;
     use super::selectors::HtmlSelectorFindFirst;
+    use super::tag_iterator::TagIterator;
     use super::tag_path::TagPathItem;
+    use super::utf8_decoder::{decode_utf8, InvalidUtf8};
+    use std::io::{self, Read};
 
     /// Count the matching of each element in the request Vector.
     /// - `html` is an HTML code
@@ -64,7 +241,20 @@ pub mod with_matcher {
     where
         F: Fn(&TagPathItem) -> bool,
     {
-        let mut html_selector = MatcherHtmlSelector::default();
+        count_with_options(html, css_requests, ParserOptions::new())
+    }
+
+    /// Same as `count` but the HTML code is read with `options`, like the decoding of the character
+    /// references or the XML mode.
+    pub fn count_with_options<F>(
+        html: &str,
+        css_requests: &[F],
+        options: ParserOptions,
+    ) -> Vec<usize>
+    where
+        F: Fn(&TagPathItem) -> bool,
+    {
+        let mut html_selector = MatcherHtmlSelector::default().options(options);
         html_selector.count(html, css_requests)
     }
 
@@ -76,14 +266,162 @@ pub mod with_matcher {
     where
         F: Fn(&TagPathItem) -> bool,
     {
-        let mut html_selector = MatcherHtmlSelector::default();
+        find_first_with_options(html, css_requests, ParserOptions::new())
+    }
+
+    /// Same as `find_first` but the HTML code is read with `options`, like the decoding of the
+    /// character references or the XML mode.
+    pub fn find_first_with_options<F>(
+        html: &str,
+        css_requests: &[F],
+        options: ParserOptions,
+    ) -> Vec<String>
+    where
+        F: Fn(&TagPathItem) -> bool,
+    {
+        let mut html_selector = MatcherHtmlSelector::default().options(options);
         html_selector.find_first(html, css_requests)
     }
+
+    /// Same as `count` but it fails on the HTML code that can not be read as an element.
+    pub fn try_count<F>(html: &str, css_requests: &[F]) -> Result<Vec<usize>, ParseError>
+    where
+        F: Fn(&TagPathItem) -> bool,
+    {
+        try_count_with_options(html, css_requests, ParserOptions::new())
+    }
+
+    /// Same as `try_count` but the HTML code is read with `options`, in strict mode.
+    pub fn try_count_with_options<F>(
+        html: &str,
+        css_requests: &[F],
+        options: ParserOptions,
+    ) -> Result<Vec<usize>, ParseError>
+    where
+        F: Fn(&TagPathItem) -> bool,
+    {
+        let options = options.strict(true);
+        let mut tag_iterator = TagIterator::new(html).options(options);
+        let mut html_selector = MatcherHtmlSelector::default().options(options);
+        let counts = html_selector.count_elements(&mut tag_iterator, css_requests);
+        tag_iterator.take_error().map_or(Ok(counts), Err)
+    }
+
+    /// Same as `find_first` but it fails on the HTML code that can not be read as an element.
+    pub fn try_find_first<F>(html: &str, css_requests: &[F]) -> Result<Vec<String>, ParseError>
+    where
+        F: Fn(&TagPathItem) -> bool,
+    {
+        try_find_first_with_options(html, css_requests, ParserOptions::new())
+    }
+
+    /// Same as `try_find_first` but the HTML code is read with `options`, in strict mode.
+    pub fn try_find_first_with_options<F>(
+        html: &str,
+        css_requests: &[F],
+        options: ParserOptions,
+    ) -> Result<Vec<String>, ParseError>
+    where
+        F: Fn(&TagPathItem) -> bool,
+    {
+        let options = options.strict(true);
+        let mut tag_iterator = TagIterator::new(html).options(options);
+        let mut html_selector = MatcherHtmlSelector::default().options(options);
+        let founds = html_selector.find_first_elements(&mut tag_iterator, css_requests);
+        tag_iterator.take_error().map_or(Ok(founds), Err)
+    }
+
+    /// Same as `count` but the HTML code is UTF-8 encoded. The bytes that are not valid UTF-8 are
+    /// read with the `invalid_utf8` policy, like `decode_utf8` does.
+    pub fn count_bytes<F>(
+        html: &[u8],
+        css_requests: &[F],
+        invalid_utf8: InvalidUtf8,
+    ) -> io::Result<Vec<usize>>
+    where
+        F: Fn(&TagPathItem) -> bool,
+    {
+        Ok(count(&decode_utf8(html, invalid_utf8)?, css_requests))
+    }
+
+    /// Same as `find_first` but the HTML code is UTF-8 encoded. The bytes that are not valid UTF-8
+    /// are read with the `invalid_utf8` policy, like `decode_utf8` does.
+    pub fn find_first_bytes<F>(
+        html: &[u8],
+        css_requests: &[F],
+        invalid_utf8: InvalidUtf8,
+    ) -> io::Result<Vec<String>>
+    where
+        F: Fn(&TagPathItem) -> bool,
+    {
+        Ok(find_first(&decode_utf8(html, invalid_utf8)?, css_requests))
+    }
+
+    /// Same as `count` but the HTML code is read chunk by chunk from `reader`.
+    pub fn count_from_reader<R, F>(reader: R, css_requests: &[F]) -> io::Result<Vec<usize>>
+    where
+        R: Read,
+        F: Fn(&TagPathItem) -> bool,
+    {
+        count_from_reader_with_options(reader, css_requests, ParserOptions::new())
+    }
+
+    /// Same as `count_from_reader` but the HTML code is read with `options`, like the decoding of
+    /// the character references or the XML mode.
+    pub fn count_from_reader_with_options<R, F>(
+        reader: R,
+        css_requests: &[F],
+        options: ParserOptions,
+    ) -> io::Result<Vec<usize>>
+    where
+        R: Read,
+        F: Fn(&TagPathItem) -> bool,
+    {
+        let mut tag_iterator = ReaderTagIterator::new(reader).options(options);
+        let mut html_selector = MatcherHtmlSelector::default().options(options);
+        let counts = html_selector.count_elements(&mut tag_iterator, css_requests);
+        tag_iterator.take_error().map_or(Ok(counts), Err)
+    }
+
+    /// Same as `find_first` but the HTML code is read chunk by chunk from `reader`.
+    pub fn find_first_from_reader<R, F>(reader: R, css_requests: &[F]) -> io::Result<Vec<String>>
+    where
+        R: Read,
+        F: Fn(&TagPathItem) -> bool,
+    {
+        find_first_from_reader_with_options(reader, css_requests, ParserOptions::new())
+    }
+
+    /// Same as `find_first_from_reader` but the HTML code is read with `options`, like the decoding
+    /// of the character references or the XML mode.
+    pub fn find_first_from_reader_with_options<R, F>(
+        reader: R,
+        css_requests: &[F],
+        options: ParserOptions,
+    ) -> io::Result<Vec<String>>
+    where
+        R: Read,
+        F: Fn(&TagPathItem) -> bool,
+    {
+        let mut tag_iterator = ReaderTagIterator::new(reader).options(options);
+        let mut html_selector = MatcherHtmlSelector::default().options(options);
+        let founds = html_selector.find_first_elements(&mut tag_iterator, css_requests);
+        tag_iterator.take_error().map_or(Ok(founds), Err)
+    }
 }
 
 pub mod by_tag_path {
+    use super::parse_error::ParseError;
+    use super::parser_options::ParserOptions;
+    use super::reader_tag_iterator::ReaderTagIterator;
     use super::selectors::tag_path_html_selector::TagPathHtmlSelector;
-    pub use crate::tag_path::TagPathItem;
+    use super::tag_iterator::TagIterator;
+    pub use crate::selectors::push_tag_path_html_selector::{
+        PushTagPathHtmlSelector, SelectorEvent,
+    };
+    pub use crate::tag_path::{EndTagRecovery, Namespace, TagPathItem};
+    use super::utf8_decoder::{decode_utf8, InvalidUtf8};
+    use std::io::{self, Read};
 
     type Predicate = dyn Fn(&TagPathItem) -> bool;
     type Matcher<'a> = &'a Vec<&'a Vec<Box<Predicate>>>;
@@ -92,14 +430,209 @@ pub mod by_tag_path {
         html: &str,
         matchers: Matcher,
     ) -> Vec<usize> {
-        let mut html_selector = TagPathHtmlSelector::default();
+        count_with_options(html, matchers, ParserOptions::new())
+    }
+
+    /// Same as `count` but the HTML code is read with `options`, like the decoding of the character
+    /// references or the XML mode.
+    pub fn count_with_options(html: &str, matchers: Matcher, options: ParserOptions) -> Vec<usize> {
+        let mut html_selector = TagPathHtmlSelector::default().options(options);
         html_selector.count(html, matchers)
     }
+
     pub fn find_first(
         html: &str,
         matchers: Matcher,
     ) -> Vec<String> {
-        let mut html_selector = TagPathHtmlSelector::default();
+        find_first_with_options(html, matchers, ParserOptions::new())
+    }
+
+    /// Same as `find_first` but the HTML code is read with `options`, like the decoding of the
+    /// character references or the XML mode.
+    pub fn find_first_with_options(
+        html: &str,
+        matchers: Matcher,
+        options: ParserOptions,
+    ) -> Vec<String> {
+        let mut html_selector = TagPathHtmlSelector::default().options(options);
         html_selector.find_first(html, matchers)
     }
+
+    /// Same as `count` but it fails on the HTML code that can not be read as an element.
+    pub fn try_count(html: &str, matchers: Matcher) -> Result<Vec<usize>, ParseError> {
+        try_count_with_options(html, matchers, ParserOptions::new())
+    }
+
+    /// Same as `try_count` but the HTML code is read with `options`, in strict mode.
+    pub fn try_count_with_options(
+        html: &str,
+        matchers: Matcher,
+        options: ParserOptions,
+    ) -> Result<Vec<usize>, ParseError> {
+        let options = options.strict(true);
+        let mut tag_iterator = TagIterator::new(html).options(options);
+        let mut html_selector = TagPathHtmlSelector::default().options(options);
+        let counts = html_selector.count_elements(&mut tag_iterator, matchers);
+        tag_iterator.take_error().map_or(Ok(counts), Err)
+    }
+
+    /// Same as `find_first` but it fails on the HTML code that can not be read as an element.
+    pub fn try_find_first(html: &str, matchers: Matcher) -> Result<Vec<String>, ParseError> {
+        try_find_first_with_options(html, matchers, ParserOptions::new())
+    }
+
+    /// Same as `try_find_first` but the HTML code is read with `options`, in strict mode.
+    pub fn try_find_first_with_options(
+        html: &str,
+        matchers: Matcher,
+        options: ParserOptions,
+    ) -> Result<Vec<String>, ParseError> {
+        let options = options.strict(true);
+        let mut tag_iterator = TagIterator::new(html).options(options);
+        let mut html_selector = TagPathHtmlSelector::default().options(options);
+        let founds = html_selector.find_first_elements(&mut tag_iterator, matchers);
+        tag_iterator.take_error().map_or(Ok(founds), Err)
+    }
+
+    /// Same as `count` but the HTML code is UTF-8 encoded. The bytes that are not valid UTF-8 are
+    /// read with the `invalid_utf8` policy, like `decode_utf8` does.
+    pub fn count_bytes(
+        html: &[u8],
+        matchers: Matcher,
+        invalid_utf8: InvalidUtf8,
+    ) -> io::Result<Vec<usize>> {
+        Ok(count(&decode_utf8(html, invalid_utf8)?, matchers))
+    }
+
+    /// Same as `find_first` but the HTML code is UTF-8 encoded. The bytes that are not valid UTF-8
+    /// are read with the `invalid_utf8` policy, like `decode_utf8` does.
+    pub fn find_first_bytes(
+        html: &[u8],
+        matchers: Matcher,
+        invalid_utf8: InvalidUtf8,
+    ) -> io::Result<Vec<String>> {
+        Ok(find_first(&decode_utf8(html, invalid_utf8)?, matchers))
+    }
+
+    /// Same as `count` but the HTML code is read chunk by chunk from `reader`.
+    pub fn count_from_reader<R: Read>(reader: R, matchers: Matcher) -> io::Result<Vec<usize>> {
+        count_from_reader_with_options(reader, matchers, ParserOptions::new())
+    }
+
+    /// Same as `count_from_reader` but the HTML code is read with `options`, like the decoding of
+    /// the character references or the XML mode.
+    pub fn count_from_reader_with_options<R: Read>(
+        reader: R,
+        matchers: Matcher,
+        options: ParserOptions,
+    ) -> io::Result<Vec<usize>> {
+        let mut tag_iterator = ReaderTagIterator::new(reader).options(options);
+        let mut html_selector = TagPathHtmlSelector::default().options(options);
+        let counts = html_selector.count_elements(&mut tag_iterator, matchers);
+        tag_iterator.take_error().map_or(Ok(counts), Err)
+    }
+
+    /// Same as `find_first` but the HTML code is read chunk by chunk from `reader`.
+    pub fn find_first_from_reader<R: Read>(
+        reader: R,
+        matchers: Matcher,
+    ) -> io::Result<Vec<String>> {
+        find_first_from_reader_with_options(reader, matchers, ParserOptions::new())
+    }
+
+    /// Same as `find_first_from_reader` but the HTML code is read with `options`, like the decoding
+    /// of the character references or the XML mode.
+    pub fn find_first_from_reader_with_options<R: Read>(
+        reader: R,
+        matchers: Matcher,
+        options: ParserOptions,
+    ) -> io::Result<Vec<String>> {
+        let mut tag_iterator = ReaderTagIterator::new(reader).options(options);
+        let mut html_selector = TagPathHtmlSelector::default().options(options);
+        let founds = html_selector.find_first_elements(&mut tag_iterator, matchers);
+        tag_iterator.take_error().map_or(Ok(founds), Err)
+    }
+
+    /// Same as `count` but the HTML code is read chunk by chunk from an async `reader`.
+    /// A `Stream` of byte chunks can be read with `futures::TryStreamExt::into_async_read`.
+    #[cfg(feature = "async")]
+    pub async fn count_from_async_reader<R>(
+        reader: R,
+        matchers: Matcher<'_>,
+    ) -> io::Result<Vec<usize>>
+    where
+        R: futures_util::io::AsyncRead + Unpin,
+    {
+        count_from_async_reader_with_options(reader, matchers, ParserOptions::new()).await
+    }
+
+    /// Same as `count_from_async_reader` but the HTML code is read with `options`, like the
+    /// decoding of the character references or the XML mode.
+    #[cfg(feature = "async")]
+    pub async fn count_from_async_reader_with_options<R>(
+        reader: R,
+        matchers: Matcher<'_>,
+        options: ParserOptions,
+    ) -> io::Result<Vec<usize>>
+    where
+        R: futures_util::io::AsyncRead + Unpin,
+    {
+        let mut html_selector = PushTagPathHtmlSelector::new(matchers).options(options);
+        feed_from_async_reader(reader, &mut html_selector).await?;
+        Ok(html_selector.counts().to_vec())
+    }
+
+    /// Same as `find_first` but the HTML code is read chunk by chunk from an async `reader`.
+    /// A `Stream` of byte chunks can be read with `futures::TryStreamExt::into_async_read`.
+    #[cfg(feature = "async")]
+    pub async fn find_first_from_async_reader<R>(
+        reader: R,
+        matchers: Matcher<'_>,
+    ) -> io::Result<Vec<String>>
+    where
+        R: futures_util::io::AsyncRead + Unpin,
+    {
+        find_first_from_async_reader_with_options(reader, matchers, ParserOptions::new()).await
+    }
+
+    /// Same as `find_first_from_async_reader` but the HTML code is read with `options`, like the
+    /// decoding of the character references or the XML mode.
+    #[cfg(feature = "async")]
+    pub async fn find_first_from_async_reader_with_options<R>(
+        reader: R,
+        matchers: Matcher<'_>,
+        options: ParserOptions,
+    ) -> io::Result<Vec<String>>
+    where
+        R: futures_util::io::AsyncRead + Unpin,
+    {
+        let mut html_selector = PushTagPathHtmlSelector::new(matchers).options(options);
+        feed_from_async_reader(reader, &mut html_selector).await?;
+        Ok(html_selector.founds().to_vec())
+    }
+
+    /// Feed the push selector with all the chunks read from the async `reader`
+    #[cfg(feature = "async")]
+    async fn feed_from_async_reader<R>(
+        mut reader: R,
+        html_selector: &mut PushTagPathHtmlSelector<'_>,
+    ) -> io::Result<()>
+    where
+        R: futures_util::io::AsyncRead + Unpin,
+    {
+        use futures_util::io::AsyncReadExt;
+
+        let mut chunk = vec![0; 8 * 1024];
+        loop {
+            let length = match reader.read(&mut chunk).await {
+                Err(error) if error.kind() == io::ErrorKind::Interrupted => continue,
+                result => result?,
+            };
+            if length == 0 {
+                html_selector.finish_bytes()?;
+                return Ok(());
+            }
+            html_selector.feed_bytes(&chunk[..length])?;
+        }
+    }
 }
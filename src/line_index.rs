@@ -0,0 +1,82 @@
+use crate::parse_error::LineColumn;
+
+/// Mapper of the positions in bytes into an HTML document, like the spans of the elements, to
+/// lines and columns. The beginnings of the lines are found once : then only the line of the
+/// position is read.
+#[derive(Debug, Clone)]
+pub struct LineIndex<'a> {
+    html: &'a str,
+    /// position of the beginning of each line
+    line_starts: Vec<usize>,
+}
+
+impl<'a> LineIndex<'a> {
+    pub fn new(html: &'a str) -> Self {
+        let line_starts = std::iter::once(0)
+            .chain(html.match_indices('\n').map(|(position, _)| position + 1))
+            .collect();
+        LineIndex { html, line_starts }
+    }
+
+    /// Line and column of `offset`. A position after the end of the HTML document is read as its
+    /// end, and a position inside a character as the beginning of the character.
+    pub fn line_column(&self, offset: usize) -> LineColumn {
+        let mut offset = offset.min(self.html.len());
+        while !self.html.is_char_boundary(offset) {
+            offset -= 1;
+        }
+        let line = self
+            .line_starts
+            .partition_point(|line_start| *line_start <= offset);
+        let line_start = self.line_starts[line - 1];
+        LineColumn {
+            line,
+            column: 1 + self.html[line_start..offset].chars().count(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_line_index {
+    use super::*;
+    use crate::tag_iterator::TagIterator;
+
+    #[test]
+    fn should_give_the_same_line_and_column_than_reading_the_whole_html() {
+        let html = "<p>\n  élève\n\n<b>x</b>";
+        let line_index = LineIndex::new(html);
+
+        for (offset, _) in html
+            .char_indices()
+            .chain(std::iter::once((html.len(), ' ')))
+        {
+            assert_eq!(
+                LineColumn::new().advance(&html[..offset]),
+                line_index.line_column(offset),
+                "offset {}",
+                offset
+            );
+        }
+        assert_eq!(
+            line_index.line_column(html.len()),
+            line_index.line_column(100)
+        );
+    }
+
+    #[test]
+    fn should_give_the_line_and_column_of_each_element() {
+        let html = "<ul>\n  <li>foo</li>\n</ul>";
+        let line_index = LineIndex::new(html);
+
+        let positions: Vec<(usize, usize)> = TagIterator::new(html)
+            .map(|element| {
+                let line_column = line_index.line_column(element.span().0);
+                (line_column.line, line_column.column)
+            })
+            .collect();
+        assert_eq!(
+            vec![(1, 1), (1, 5), (2, 3), (2, 7), (2, 10), (2, 15), (3, 1)],
+            positions
+        );
+    }
+}
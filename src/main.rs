@@ -58,7 +58,7 @@ fn main() {
     println!("Parsing execution time: {} ms ({} micro-seconds)", now.elapsed().as_millis(), now.elapsed().as_micros());
     println!(
         "Command number {:?} for a total of {:?}\n- item {:?} price {:?}",
-        founds.get(0),
+        founds.first(),
         founds.get(1),
         founds.get(2),
         founds.get(3)
@@ -0,0 +1,125 @@
+use std::fmt;
+
+/// Kind of HTML code that can not be read as an element
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseErrorKind {
+    /// A comment without `-->`, like `<!-- foo`
+    UnterminatedComment,
+    /// A tag without `>`, like `<div` or `</div`
+    UnterminatedTag,
+    /// A `<` that does not start an element, like `< 3` or `<123`
+    InvalidMarkup,
+}
+
+impl fmt::Display for ParseErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let description = match self {
+            ParseErrorKind::UnterminatedComment => "unterminated comment",
+            ParseErrorKind::UnterminatedTag => "unterminated tag",
+            ParseErrorKind::InvalidMarkup => "invalid markup",
+        };
+        f.write_str(description)
+    }
+}
+
+/// Error of the HTML parsing : the HTML code at `offset` can not be read as an element
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub kind: ParseErrorKind,
+    /// position in bytes into the whole HTML document
+    pub offset: usize,
+    /// line of `offset`, starting at 1
+    pub line: usize,
+    /// column of `offset` in characters, starting at 1
+    pub column: usize,
+}
+
+impl ParseError {
+    /// Error at the end of `html_before`, the HTML code read before the error
+    pub fn new(kind: ParseErrorKind, html_before: &str) -> Self {
+        ParseError::after(
+            kind,
+            html_before.len(),
+            LineColumn::new().advance(html_before),
+        )
+    }
+
+    /// Error at `offset`, whose line and column are `line_column`
+    pub(crate) fn after(kind: ParseErrorKind, offset: usize, line_column: LineColumn) -> Self {
+        ParseError {
+            kind,
+            offset,
+            line: line_column.line,
+            column: line_column.column,
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} at line {}, column {} (byte {})",
+            self.kind, self.line, self.column, self.offset
+        )
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Line and column of a position into the HTML code, both starting at 1. The column is counted
+/// in characters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LineColumn {
+    pub line: usize,
+    pub column: usize,
+}
+
+impl LineColumn {
+    pub fn new() -> Self {
+        LineColumn { line: 1, column: 1 }
+    }
+
+    /// Line and column after reading `html` from this position
+    pub fn advance(mut self, html: &str) -> Self {
+        match html.rfind('\n') {
+            Some(position) => {
+                self.line += html.bytes().filter(|c| *c == b'\n').count();
+                self.column = 1 + html[position + 1..].chars().count();
+            }
+            None => self.column += html.chars().count(),
+        }
+        self
+    }
+}
+
+impl Default for LineColumn {
+    fn default() -> Self {
+        LineColumn::new()
+    }
+}
+
+#[cfg(test)]
+mod test_parse_error {
+    use super::*;
+
+    #[test]
+    fn should_give_the_line_and_the_column_of_the_error() {
+        let error = ParseError::new(ParseErrorKind::UnterminatedTag, "<p>\n  é <b>\n  ");
+
+        assert_eq!(15, error.offset);
+        assert_eq!(3, error.line);
+        assert_eq!(3, error.column);
+        assert_eq!(
+            "unterminated tag at line 3, column 3 (byte 15)",
+            error.to_string()
+        );
+    }
+
+    #[test]
+    fn should_advance_the_line_and_the_column_chunk_by_chunk() {
+        let line_column = LineColumn::new().advance("<p>\n  é").advance(" <b>\n  ");
+        assert_eq!(LineColumn::new().advance("<p>\n  é <b>\n  "), line_column);
+        assert_eq!(LineColumn { line: 3, column: 3 }, line_column);
+    }
+}
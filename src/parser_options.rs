@@ -0,0 +1,187 @@
+//! Options of the HTML parsing shared by the tag iterator and by all the selectors, so they read
+//! the same HTML code the same way.
+
+use crate::elements::start_element::is_void_element;
+use crate::tag_iterator::Token;
+
+/// How the white spaces of the contents found are read
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Whitespace {
+    /// Replace each line break with a space
+    #[default]
+    ReplaceLineBreaks,
+    /// Keep the white spaces as written
+    Preserve,
+    /// Replace each sequence of white spaces with a single space, and remove the white spaces at
+    /// the beginning and at the end, like a browser displays a text
+    Collapse,
+}
+
+/// Options of the HTML parsing. The default options read the HTML code like a browser does.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParserOptions {
+    pub(crate) decode_character_references: bool,
+    pub(crate) whitespace: Whitespace,
+    pub(crate) skip_whitespace_texts: bool,
+    pub(crate) void_elements: bool,
+    pub(crate) implied_end_tags: bool,
+    pub(crate) fold_case: bool,
+    pub(crate) max_content_length: Option<usize>,
+    pub(crate) xml: bool,
+    pub(crate) strict: bool,
+}
+
+impl Default for ParserOptions {
+    fn default() -> Self {
+        ParserOptions::new()
+    }
+}
+
+impl ParserOptions {
+    pub fn new() -> Self {
+        ParserOptions {
+            decode_character_references: false,
+            whitespace: Whitespace::default(),
+            skip_whitespace_texts: false,
+            void_elements: true,
+            implied_end_tags: true,
+            fold_case: true,
+            max_content_length: None,
+            xml: false,
+            strict: false,
+        }
+    }
+
+    /// Decode the character references, like `&eacute;`, of the texts, of the contents found and
+    /// of the attribute values matched
+    pub fn decode_character_references(mut self, decode: bool) -> Self {
+        self.decode_character_references = decode;
+        self
+    }
+
+    /// Read the white spaces of the contents found with the given policy
+    pub fn whitespace(mut self, whitespace: Whitespace) -> Self {
+        self.whitespace = whitespace;
+        self
+    }
+
+    /// Skip the texts made of white spaces only, like the indentation between 2 tags
+    pub fn skip_whitespace_texts(mut self, skip: bool) -> Self {
+        self.skip_whitespace_texts = skip;
+        self
+    }
+
+    /// Read the void elements, like `<br>` or `<img>`, as elements without end tag. Otherwise an
+    /// element has no end tag only when it is written like `<br/>`.
+    pub fn void_elements(mut self, void_elements: bool) -> Self {
+        self.void_elements = void_elements;
+        self
+    }
+
+    /// Close the elements whose end tag is optional when the next element implies it, like a
+    /// `<li>` closes an open `<li>`
+    pub fn implied_end_tags(mut self, implied_end_tags: bool) -> Self {
+        self.implied_end_tags = implied_end_tags;
+        self
+    }
+
+    /// Lowercase the names of the HTML elements and of their attributes, like an HTML parser does
+    pub fn fold_case(mut self, fold_case: bool) -> Self {
+        self.fold_case = fold_case;
+        self
+    }
+
+    /// Keep at most `max_content_length` bytes of HTML code for each content found : the rest of
+    /// the content is dropped, so a huge element does not fill the memory
+    pub fn max_content_length(mut self, max_content_length: Option<usize>) -> Self {
+        self.max_content_length = max_content_length;
+        self
+    }
+
+    /// Read the HTML code as XML, like an XHTML document or an RSS feed : the names are
+    /// case-sensitive, any element may be written like `<div/>` to have no end tag, no end tag is
+    /// implied, the CDATA sections are read as texts and the processing instructions, like
+    /// `<?xml version="1.0"?>`, are read as elements. The content of `script` and `style` is read
+    /// as XML code too.
+    pub fn xml(mut self, xml: bool) -> Self {
+        self.xml = xml;
+        self
+    }
+
+    /// Fail on the HTML code that can not be read as an element, like `< 3` or `<!foo>`. By
+    /// default, this HTML code is read like a browser does : as a text or as a comment.
+    pub fn strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    /// Returns true if the element named `tag_name` has no end tag, like `<br>`. There is no void
+    /// element in XML.
+    pub(crate) fn is_void_element(&self, tag_name: &str) -> bool {
+        !self.xml && self.void_elements && is_void_element(tag_name)
+    }
+
+    /// Returns true if the tag named `tag_name` has no end tag : it is written like `<div/>`, then
+    /// `is_autoclosing` is true, or it is a void element like `<br>`
+    pub(crate) fn is_self_closing(&self, tag_name: &str, is_autoclosing: bool) -> bool {
+        is_autoclosing || self.is_void_element(tag_name)
+    }
+
+    /// Returns true if a start tag may close the elements whose end tag is optional. No end tag
+    /// is implied in XML.
+    pub(crate) fn has_implied_end_tags(&self) -> bool {
+        !self.xml && self.implied_end_tags
+    }
+
+    /// Returns true if the names of the HTML elements are lowercased. The names keep their case
+    /// in XML.
+    pub(crate) fn has_fold_case(&self) -> bool {
+        !self.xml && self.fold_case
+    }
+
+    /// Returns true if the token is read, false if it is skipped, like a text made of white
+    /// spaces only
+    pub(crate) fn is_read(&self, token: &Token<'_>) -> bool {
+        match token {
+            Token::Text(text, _, _) if self.skip_whitespace_texts => {
+                !text.chars().all(char::is_whitespace)
+            }
+            _ => true,
+        }
+    }
+
+    /// Apply the white space policy to a content found
+    pub(crate) fn format_whitespace(&self, content: &str) -> String {
+        match self.whitespace {
+            Whitespace::ReplaceLineBreaks => content.replace('\n', " "),
+            Whitespace::Preserve => content.to_string(),
+            Whitespace::Collapse => content.split_whitespace().collect::<Vec<_>>().join(" "),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_parser_options {
+    use super::*;
+
+    #[test]
+    fn should_read_the_white_spaces_with_the_given_policy() {
+        let content = "  foo\n  bar ";
+        let options = ParserOptions::new();
+        assert_eq!("  foo   bar ", options.format_whitespace(content));
+        let options = options.whitespace(Whitespace::Preserve);
+        assert_eq!(content, options.format_whitespace(content));
+        let options = options.whitespace(Whitespace::Collapse);
+        assert_eq!("foo bar", options.format_whitespace(content));
+    }
+
+    #[test]
+    fn should_skip_the_texts_made_of_white_spaces_only_when_asked() {
+        let blank = Token::Text("\n  ", 0, 3);
+        let text = Token::Text(" a ", 0, 3);
+        assert!(ParserOptions::new().is_read(&blank));
+        let options = ParserOptions::new().skip_whitespace_texts(true);
+        assert!(!options.is_read(&blank));
+        assert!(options.is_read(&text));
+    }
+}
@@ -0,0 +1,229 @@
+use std::io::{self, Read};
+
+use crate::html_buffer::HtmlBuffer;
+use crate::parser_options::ParserOptions;
+use crate::tag_iterator::{Elements, Token, TokenSource};
+
+/// size of the chunks read from the reader
+const CHUNK_SIZE: usize = 8 * 1024;
+
+/// Iterator over the HTML elements of an HTML document read chunk by chunk from a reader. Only
+/// the chunks of the elements not read yet are kept in memory.
+///
+/// The iteration stops at the first IO error. The error is available with `take_error`.
+pub struct ReaderTagIterator<R: Read> {
+    reader: R,
+    buffer: HtmlBuffer,
+    chunk: Vec<u8>,
+    is_eof: bool,
+    error: Option<io::Error>,
+}
+
+impl<R: Read> ReaderTagIterator<R> {
+    pub fn new(reader: R) -> Self {
+        ReaderTagIterator::with_chunk_size(reader, CHUNK_SIZE)
+    }
+
+    pub fn with_chunk_size(reader: R, chunk_size: usize) -> Self {
+        ReaderTagIterator {
+            reader,
+            buffer: HtmlBuffer::new(),
+            chunk: vec![0; chunk_size.max(1)],
+            is_eof: false,
+            error: None,
+        }
+    }
+
+    /// Read the elements with `options` like the tag iterator does, like the decoding of the
+    /// character references, or the strict mode where the HTML code that can not be read as an
+    /// element stops the iteration with an `io::ErrorKind::InvalidData` error
+    pub fn options(mut self, options: ParserOptions) -> Self {
+        self.buffer = self.buffer.options(options);
+        self
+    }
+
+    /// Returns the IO error that stopped the iteration, if any.
+    pub fn take_error(&mut self) -> Option<io::Error> {
+        self.error.take()
+    }
+
+    /// Read the chunks until the buffer contains a complete element. It returns false at the end
+    /// of the HTML code, or once the iteration is stopped by an error.
+    fn fill_buffer(&mut self) -> bool {
+        loop {
+            if self.error.is_some() {
+                return false;
+            }
+            if self.buffer.has_next_element(self.is_eof) {
+                return true;
+            }
+            if self.is_eof {
+                return false;
+            }
+            if let Err(error) = self.read_chunk() {
+                self.error = Some(error);
+            }
+        }
+    }
+
+    /// Read the next chunk of HTML code into the buffer
+    fn read_chunk(&mut self) -> io::Result<()> {
+        let length = loop {
+            match self.reader.read(&mut self.chunk) {
+                Err(error) if error.kind() == io::ErrorKind::Interrupted => continue,
+                result => break result?,
+            }
+        };
+        if length == 0 {
+            self.is_eof = true;
+            self.buffer.finish_bytes()?;
+        } else {
+            self.buffer.push_bytes(&self.chunk[..length])?;
+        }
+        Ok(())
+    }
+}
+
+impl<R: Read> Iterator for ReaderTagIterator<R> {
+    type Item = Elements;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if !self.fill_buffer() {
+            return None;
+        }
+        match self.buffer.next_element(self.is_eof) {
+            Ok(element) => element,
+            Err(error) => {
+                self.error = Some(io::Error::new(io::ErrorKind::InvalidData, error));
+                None
+            }
+        }
+    }
+}
+
+impl<R: Read> TokenSource for ReaderTagIterator<R> {
+    fn next_token_html(&mut self) -> Option<(Token<'_>, &str)> {
+        if !self.fill_buffer() {
+            return None;
+        }
+        match self.buffer.next_token_html(self.is_eof) {
+            Ok(token) => token,
+            Err(error) => {
+                self.error = Some(io::Error::new(io::ErrorKind::InvalidData, error));
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_reader_tag_iterator {
+    use super::*;
+    use crate::tag_iterator::TagIterator;
+
+    #[test]
+    fn should_read_the_same_elements_than_the_tag_iterator_whatever_the_chunk_size() {
+        let html = std::fs::read_to_string("tables.html").unwrap();
+        let expected_elements: Vec<Elements> = TagIterator::new(&html).collect();
+
+        for chunk_size in &[1, 2, 3, 7, 64, 8 * 1024] {
+            let tag_iterator = ReaderTagIterator::with_chunk_size(html.as_bytes(), *chunk_size);
+            let elements: Vec<Elements> = tag_iterator.collect();
+            assert_eq!(expected_elements, elements, "chunk size {}", chunk_size);
+        }
+    }
+
+    #[test]
+    fn should_read_the_content_of_a_script_split_between_chunks() {
+        let html = "<p><script>if (a</b) {}</scrip</script ></p><title>&lt;</TITLE>";
+        let expected_elements: Vec<Elements> = TagIterator::new(html).collect();
+
+        for chunk_size in 1..html.len() {
+            let tag_iterator = ReaderTagIterator::with_chunk_size(html.as_bytes(), chunk_size);
+            let elements: Vec<Elements> = tag_iterator.collect();
+            assert_eq!(expected_elements, elements, "chunk size {}", chunk_size);
+        }
+        assert_eq!(
+            Elements::Text("if (a</b) {}</scrip".to_string(), 11, 30),
+            expected_elements[2]
+        );
+    }
+
+    #[test]
+    fn should_read_unrecognized_markup_split_between_chunks() {
+        let html = "<p>1 < 3 <123 <!foo> <?xml?></ div><!--><</p><!-->--><div class='a";
+        let expected_elements: Vec<Elements> = TagIterator::new(html).collect();
+
+        for chunk_size in 1..html.len() {
+            let tag_iterator = ReaderTagIterator::with_chunk_size(html.as_bytes(), chunk_size);
+            let elements: Vec<Elements> = tag_iterator.collect();
+            assert_eq!(expected_elements, elements, "chunk size {}", chunk_size);
+        }
+    }
+
+    #[test]
+    fn should_read_non_ascii_characters_split_between_chunks() {
+        let html = "<p title='élève'>€ 61,90</p><café data-é=ü>é</café>été";
+        let expected_elements: Vec<Elements> = TagIterator::new(html).collect();
+
+        for chunk_size in 1..html.len() {
+            let tag_iterator = ReaderTagIterator::with_chunk_size(html.as_bytes(), chunk_size);
+            let elements: Vec<Elements> = tag_iterator.collect();
+            assert_eq!(expected_elements, elements, "chunk size {}", chunk_size);
+        }
+    }
+
+    #[test]
+    fn should_give_the_html_code_of_the_last_element() {
+        let html = "<div id='foo'>bar</div>";
+        let mut tag_iterator = ReaderTagIterator::with_chunk_size(html.as_bytes(), 4);
+
+        let mut elements_html = vec![];
+        while let Some((_, html)) = tag_iterator.next_token_html() {
+            elements_html.push(html.to_string());
+        }
+
+        assert_eq!(vec!["<div id='foo'>", "bar", "</div>"], elements_html);
+    }
+
+    #[test]
+    fn should_read_an_unterminated_comment_until_the_end() {
+        let html = "<p>foo</p>\n<!-- bar";
+        let tag_iterator = ReaderTagIterator::with_chunk_size(html.as_bytes(), 4);
+        let elements: Vec<Elements> = tag_iterator.collect();
+
+        assert_eq!(5, elements.len());
+        assert_eq!(Elements::Comment(" bar".to_string(), 11, 19), elements[4]);
+    }
+
+    #[test]
+    fn should_stop_at_the_html_code_that_can_not_be_read_in_strict_mode() {
+        let html = "<p>1 < 3</p>";
+        let options = ParserOptions::new().strict(true);
+        let mut tag_iterator =
+            ReaderTagIterator::with_chunk_size(html.as_bytes(), 4).options(options);
+
+        assert!(matches!(
+            tag_iterator.next(),
+            Some(Elements::Start(_, 0, 3))
+        ));
+        assert!(matches!(tag_iterator.next(), Some(Elements::Text(..))));
+        assert_eq!(None, tag_iterator.next());
+        let error = tag_iterator.take_error().expect("a parse error");
+        assert_eq!(io::ErrorKind::InvalidData, error.kind());
+    }
+
+    #[test]
+    fn should_stop_at_the_first_io_error() {
+        let html: &[u8] = &[b'<', b'p', b'>', 0xff];
+        let mut tag_iterator = ReaderTagIterator::with_chunk_size(html, 3);
+
+        assert!(matches!(
+            tag_iterator.next(),
+            Some(Elements::Start(_, 0, 3))
+        ));
+        assert_eq!(None, tag_iterator.next());
+        let error = tag_iterator.take_error().expect("an invalid UTF-8 error");
+        assert_eq!(io::ErrorKind::InvalidData, error.kind());
+    }
+}
@@ -13,6 +13,22 @@ macro_rules! css_selector {
             $tag_name
         )))
     };
+    // NAMESPACE Selectors
+    (* | $tag_name:tt) => {
+        $crate::selectors::selector_predicates::local_name_predicate(String::from(stringify!(
+            $tag_name
+        )))
+    };
+    ($namespace:tt | $tag_name:tt) => {
+        $crate::selectors::selector_predicates::and_predicate(vec![
+            $crate::selectors::selector_predicates::namespace_predicate(String::from(stringify!(
+                $namespace
+            ))),
+            $crate::selectors::selector_predicates::local_name_predicate(String::from(stringify!(
+                $tag_name
+            ))),
+        ])
+    };
     // ID Selectors
     (# $id:tt) => {
         $crate::selectors::selector_predicates::id_predicate(String::from(stringify!($id)))
@@ -63,6 +79,17 @@ macro_rules! css_selector {
             ),
         ])
     };
+    ($tag_name:tt [$attribute_name:tt = $attribute_value:literal i]) => {
+        $crate::selectors::selector_predicates::and_predicate(vec![
+            $crate::selectors::selector_predicates::tag_name_predicate(String::from(stringify!(
+                $tag_name
+            ))),
+            $crate::selectors::selector_predicates::attribute_equals_ignore_case_predicate(
+                String::from(stringify!($attribute_name)),
+                String::from($attribute_value),
+            ),
+        ])
+    };
     ($tag_name:tt [$attribute_name:tt ^= $attribute_value:literal ]) => {
         $crate::selectors::selector_predicates::and_predicate(vec![
             $crate::selectors::selector_predicates::tag_name_predicate(String::from(stringify!(
@@ -102,13 +129,15 @@ macro_rules! css_selector {
 #[cfg(test)]
 mod test_css_selector_macro {
     use crate::elements::{start_element::Tag, Element};
-    use crate::tag_path::TagPathItem;
+    use crate::tag_path::{Namespace, TagPathItem};
 
     fn create_tag(html: &str) -> TagPathItem {
         let tag = Tag::extract(html).expect("invalid HTML code to create tag in the tests");
+        let namespace = Namespace::of_element(None, &tag.name);
         TagPathItem {
             tag: Box::new(tag),
             nth_child: 1,
+            namespace,
         }
     }
 
@@ -193,6 +222,16 @@ mod test_css_selector_macro {
         assert!(!matcher(&unmatched_tag_path_item));
     }
     #[test]
+    fn should_test_macro_given_tag_name_and_attribute_equals_value_ignoring_case() {
+        let matched_tag_path_item = create_tag("<input type='TEXT'>");
+        let unmatched_tag_path_item = create_tag("<input type='texts'>");
+
+        let matcher = css_selector!(input[type = "text" i]);
+
+        assert!(matcher(&matched_tag_path_item));
+        assert!(!matcher(&unmatched_tag_path_item));
+    }
+    #[test]
     fn should_test_macro_given_tag_name_and_attribute_ends_with_vallue() {
         let matched_tag_path_item = create_tag("<div class='foo'>");
         let unmatched_tag_path_item = create_tag("<h1>");
@@ -220,4 +259,21 @@ mod test_css_selector_macro {
 
         assert!(matcher(&tag_path_item));
     }
+    #[test]
+    fn should_match_namespace_and_local_name() {
+        let mut rect = create_tag("<rect>");
+        rect.namespace = Namespace::Svg;
+        let prefixed_rect = create_tag("<svg:rect>");
+        let html_rect = create_tag("<rect>");
+
+        let matcher = css_selector!(svg | rect);
+        assert!(matcher(&rect));
+        assert!(matcher(&prefixed_rect));
+        assert!(!matcher(&html_rect));
+
+        let matcher = css_selector!(*|rect);
+        assert!(matcher(&rect));
+        assert!(matcher(&prefixed_rect));
+        assert!(matcher(&html_rect));
+    }
 }
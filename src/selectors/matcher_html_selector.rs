@@ -1,13 +1,58 @@
 use crate::selectors::HtmlSelectorCounter;
 use crate::selectors::HtmlSelectorFindFirst;
-use crate::tag_iterator::Elements;
-use crate::tag_iterator::TagIterator;
-use crate::tag_path::TagPathItem;
-
-pub struct MatcherHtmlSelector {}
+use crate::tag_iterator::{TagIterator, Token, TokenSource};
+use crate::elements::start_element::TagRef;
+use crate::parser_options::ParserOptions;
+use crate::tag_path::{TagPath, TagPathItem};
+
+pub struct MatcherHtmlSelector {
+    options: ParserOptions,
+    /// open elements, giving the namespace and the position among its siblings of each tag read
+    path: TagPath,
+}
 impl MatcherHtmlSelector {
     fn new() -> Self {
-        MatcherHtmlSelector {}
+        MatcherHtmlSelector {
+            options: ParserOptions::new(),
+            path: TagPath::new(),
+        }
+    }
+
+    /// Read the HTML code with `options`, like the white space policy of the contents found, the
+    /// case folding of the names or the XML mode
+    pub fn options(mut self, options: ParserOptions) -> Self {
+        self.options = options;
+        self.path = TagPath::new().options(options);
+        self
+    }
+
+    /// Add a start tag to the open elements, then returns, for each matcher, true if it matches
+    /// the tag. The tag is read with its parents : its namespace and its position among its
+    /// siblings are known.
+    fn read_start_tag<F>(&mut self, tag: TagRef<'_>, matchers: &[F]) -> Vec<bool>
+    where
+        F: Fn(&TagPathItem) -> bool,
+    {
+        self.path.add(tag);
+        let matching = match self.path.last() {
+            Some(tag_path_item) => matchers.iter().map(|matcher| matcher(tag_path_item)).collect(),
+            None => vec![false; matchers.len()],
+        };
+        self.path.close_self_closing_tag();
+        matching
+    }
+
+    /// Forget the open elements of the HTML code read before, to read a new HTML document
+    fn reset(&mut self) {
+        self.path = TagPath::new().options(self.options);
+    }
+
+    /// Close the open element of an end tag. In HTML, the end tag of a void element, like
+    /// `</br>`, is ignored.
+    fn read_end_tag(&mut self, tag_name: &str) {
+        if !self.path.is_ignored_end_tag(tag_name) {
+            self.path.close(tag_name);
+        }
     }
 }
 impl Default for MatcherHtmlSelector {
@@ -16,66 +61,66 @@ impl Default for MatcherHtmlSelector {
     }
 }
 
-/// HTML matcher that only use 1 predicate for the last tag path item.
-/// It returns the count of element that match
-/// **nth-child predicate is not supported**
-impl<F> HtmlSelectorCounter<F> for MatcherHtmlSelector
-where
-    F: Fn(&TagPathItem) -> bool,
-{
-    fn count(&mut self, html: &str, matchers: &[F]) -> Vec<usize> {
+impl MatcherHtmlSelector {
+    /// Count the elements read from `elements` that match the 1st predicate
+    pub(crate) fn count_elements<S, F>(&mut self, mut elements: S, matchers: &[F]) -> Vec<usize>
+    where
+        S: TokenSource,
+        F: Fn(&TagPathItem) -> bool,
+    {
+        self.reset();
         let mut count = 0;
-        let matcher = &matchers[0];
-
-        let tag_iterator = TagIterator::new(html);
-        tag_iterator.for_each(|element| 
-            if let Elements::Start(tag, _begin, _end) = element {
-                let tag_path_item = TagPathItem {
-                    tag: Box::new(tag),
-                    nth_child: 0, //FIXME
-                };
-                if matcher(&tag_path_item) {
-                    count += 1;
+        let matcher = match matchers.first() {
+            Some(matcher) => matcher,
+            None => return vec![],
+        };
+
+        while let Some((token, _html)) = elements.next_token_html() {
+            match token {
+                Token::Start(tag, _begin, _end) => {
+                    let matching = self.read_start_tag(tag, std::slice::from_ref(matcher));
+                    if matching.contains(&true) {
+                        count += 1;
+                    }
                 }
+                Token::End(tag_name, _begin, _end) => self.read_end_tag(&tag_name),
+                _ => {}
             }
-        );
+        }
 
         vec![count]
     }
-}
 
-/// HTML matcher that only use 1 predicate for the last tag path item.
-/// It returns the 1st text of element that match
-/// **nth-child predicate is not supported**
-impl<F> HtmlSelectorFindFirst<F> for MatcherHtmlSelector
-where
-    F: Fn(&TagPathItem) -> bool,
-{
-    fn find_first(&mut self, html: &str, matchers: &[F]) -> Vec<String> {
-        
+    /// Returns the content of the 1st element matching each predicate, read from `elements`
+    pub(crate) fn find_first_elements<S, F>(&mut self, mut elements: S, matchers: &[F]) -> Vec<String>
+    where
+        S: TokenSource,
+        F: Fn(&TagPathItem) -> bool,
+    {
+        self.reset();
         let mut founds = vec![String::new(); matchers.len()];
-        let mut text_store = super::FindFirstTextStore::new(matchers.len());
-
-        let tag_iterator = TagIterator::new(html);
-        for element in tag_iterator {
-            match element {
-                Elements::Start(tag, _begin, end) => {
-                    let tag_path_item = TagPathItem {
-                        tag: Box::new(tag),
-                        nth_child: 0, // FIXME don't need.
-                    };
-                    matchers.iter()
+        let mut text_store = super::FindFirstTextStore::new(matchers.len())
+            .options(self.options);
+
+        while let Some((token, html)) = elements.next_token_html() {
+            match token {
+                Token::Start(tag, _begin, _end) => {
+                    text_store.push_element_html(html);
+                    self.read_start_tag(tag, matchers)
+                        .into_iter()
                         .enumerate()
-                        .for_each(|(index, predicate)| {
-                            if predicate(&tag_path_item) {
-                                text_store.store_starting_position(index, end);
+                        .for_each(|(index, does_match)| {
+                            if does_match {
+                                text_store.store_starting_position(index);
                             }
                         })
                 }
-                Elements::End(_name, begin, _end) => {
-                    text_store.update_content(&mut founds, begin, html);
+                Token::End(tag_name, _begin, _end) => {
+                    self.read_end_tag(&tag_name);
+                    text_store.update_content(&mut founds);
+                    text_store.push_element_html(html);
                 }
-                _ => {}
+                token => text_store.push_content_html(&token, html),
             }
         }
 
@@ -83,6 +128,30 @@ where
     }
 }
 
+/// HTML matcher that only use 1 predicate for the last tag path item.
+/// It returns the count of element that match
+impl<F> HtmlSelectorCounter<F> for MatcherHtmlSelector
+where
+    F: Fn(&TagPathItem) -> bool,
+{
+    fn count(&mut self, html: &str, matchers: &[F]) -> Vec<usize> {
+        let tag_iterator = TagIterator::new(html).options(self.options);
+        self.count_elements(tag_iterator, matchers)
+    }
+}
+
+/// HTML matcher that only use 1 predicate for the last tag path item.
+/// It returns the 1st text of element that match
+impl<F> HtmlSelectorFindFirst<F> for MatcherHtmlSelector
+where
+    F: Fn(&TagPathItem) -> bool,
+{
+    fn find_first(&mut self, html: &str, matchers: &[F]) -> Vec<String> {
+        let tag_iterator = TagIterator::new(html).options(self.options);
+        self.find_first_elements(tag_iterator, matchers)
+    }
+}
+
 #[macro_use]
 #[cfg(test)]
 mod test_matcher_selector {
@@ -112,6 +181,52 @@ mod test_matcher_selector {
         assert_eq!(vec![1], count);
     }
 
+    #[test]
+    fn should_keep_the_case_of_the_names_with_the_xml_options() {
+        let xml = "<feed><Entry>foo</Entry><entry>bar</entry></feed>";
+        let entry_matcher = css_selector!(Entry);
+
+        let mut html_selector = MatcherHtmlSelector::new();
+        assert_eq!(vec![0], html_selector.count(xml, &[&entry_matcher]));
+
+        let options = ParserOptions::new().xml(true);
+        let mut html_selector = MatcherHtmlSelector::new().options(options);
+        assert_eq!(vec![1], html_selector.count(xml, &[&entry_matcher]));
+        assert_eq!(vec!["foo".to_string()], html_selector.find_first(xml, &[&entry_matcher]));
+    }
+
+    #[test]
+    fn should_read_the_tags_with_their_parents() {
+        let html = "<div><svg><rect/></svg></div><ul><li>a</li><li>b</li></ul>";
+        let rect_matcher = css_selector!(svg | rect);
+        let html_rect_matcher = css_selector!(html | rect);
+        let second_item_matcher = css_selector!(li:nth-child(2));
+
+        let mut html_selector = MatcherHtmlSelector::new();
+        assert_eq!(vec![1], html_selector.count(html, &[&rect_matcher]));
+        assert_eq!(vec![0], html_selector.count(html, &[&html_rect_matcher]));
+        assert_eq!(vec![1], html_selector.count(html, &[&second_item_matcher]));
+        assert_eq!(
+            vec!["b".to_string()],
+            html_selector.find_first(html, &[&second_item_matcher])
+        );
+    }
+
+    #[test]
+    fn should_read_each_document_from_the_root() {
+        let second_item_matcher = css_selector!(li:nth-child(2));
+
+        let mut html_selector = MatcherHtmlSelector::new();
+        // the `<ul>` is not closed, but the next document does not read its `<li>` inside it
+        assert_eq!(vec![0], html_selector.count("<ul><li>a</li>", &[&second_item_matcher]));
+        assert_eq!(vec![0], html_selector.count("<li>b</li>", &[&second_item_matcher]));
+        html_selector.count("<ul><li>a</li>", &[&second_item_matcher]);
+        assert_eq!(
+            vec![String::new()],
+            html_selector.find_first("<li>b</li>", &[&second_item_matcher])
+        );
+    }
+
     #[test]
     fn should_find_first_simple_content() {
         let html = r#"
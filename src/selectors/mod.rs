@@ -1,9 +1,15 @@
 pub mod css_selector_macro;
 pub mod matcher_html_selector;
+pub mod push_tag_path_html_selector;
 pub mod selector_predicates;
 pub mod tag_name_html_selector;
 pub mod tag_path_html_selector;
 
+use crate::character_reference::decode_character_references;
+use crate::parser_options::ParserOptions;
+use crate::tag_iterator::Token;
+pub use crate::tag_path::EndTagRecovery;
+
 pub trait HtmlSelectorCounter<T> {
     fn count(&mut self, html: &str, css_requests: &[T]) -> Vec<usize>;
 }
@@ -24,46 +30,101 @@ pub fn format_css_request(css_requests: &[&str]) -> Vec<String> {
         .collect()
 }
 
+/// Store the HTML code read after a matching start tag, until an end tag is read.
 pub struct FindFirstTextStore {
-    reading_positions: Vec<Option<usize>>
+    /// HTML code read since the matching start tag, for each CSS request
+    contents: Vec<Option<String>>,
+    /// options reading the contents, like the white space policy
+    options: ParserOptions,
 }
 impl FindFirstTextStore { // FIXME test it
     fn new(capacity: usize) -> Self {
         FindFirstTextStore {
-            reading_positions: vec![None; capacity],
+            contents: vec![None; capacity],
+            options: ParserOptions::new(),
+        }
+    }
+    /// Read the contents with `options`, like decoding their character references
+    fn options(mut self, options: ParserOptions) -> Self {
+        self.options = options;
+        self
+    }
+    /// Start storing the HTML code read after the start tag matching the CSS request at `matcher_index`
+    pub fn store_starting_position(&mut self, matcher_index: usize) {
+        if let Some(content) = self.contents.get_mut(matcher_index) {
+            *content = Some(String::new());
         }
     }
-    pub fn store_starting_position(&mut self, matcher_index: usize, content_start_index: usize) {
-        if let Some(position) = self.reading_positions.get_mut(matcher_index) {
-            *position = Some(content_start_index);
+
+    /// Add the HTML code of an element to the content of every matching CSS request
+    pub fn push_element_html(&mut self, html: &str) {
+        let max_content_length = self.options.max_content_length;
+        self.contents.iter_mut().flatten().for_each(|content| {
+            let html = match max_content_length {
+                Some(max_length) => truncate(html, max_length.saturating_sub(content.len())),
+                None => html,
+            };
+            content.push_str(html);
+        });
+    }
+
+    /// Add the HTML code of an element that is not a tag. A CDATA section, read as a text in XML,
+    /// adds its text only, without `<![CDATA[` and `]]>`. The elements skipped by the options,
+    /// like the texts made of white spaces only, add nothing.
+    pub fn push_content_html(&mut self, token: &Token<'_>, html: &str) {
+        match token {
+            _ if !self.options.is_read(token) => {}
+            Token::Text(text, _, _) if html.starts_with("<![CDATA[") => {
+                self.push_element_html(text)
+            }
+            _ => self.push_element_html(html),
         }
     }
 
-    pub fn update_content(&self, founds: &mut Vec<String>, content_end_index: usize, html: &str) {
-        
-        for position in self.reading_positions.iter().enumerate() {
-            if let (index, Some(start_text)) = position {
-                let content = html.get(*start_text..content_end_index);
-                if let Some(content) = content {
-                    if let Some(value) = founds.get_mut(index) {
-                        // fill the content only if it was not filled before
-                        if value.is_empty() {
-                            value.push_str(content.to_string().replace("\n", " ").as_str());
-                        }
+    /// Fill the content of the CSS requests not filled before. It returns the indexes of the CSS
+    /// requests filled by this call.
+    pub fn update_content(&mut self, founds: &mut [String]) -> Vec<usize> {
+        let mut filled_indexes = vec![];
+        for (index, content) in self.contents.iter_mut().enumerate() {
+            if let (Some(text), Some(value)) = (content.as_ref(), founds.get_mut(index)) {
+                // fill the content only if it was not filled before
+                if value.is_empty() {
+                    let text = self.options.format_whitespace(text);
+                    if self.options.decode_character_references {
+                        value.push_str(&decode_character_references(&text));
+                    } else {
+                        value.push_str(&text);
+                    }
+                    if !value.is_empty() {
+                        filled_indexes.push(index);
                     }
                 }
+                if !value.is_empty() {
+                    *content = None;
+                }
             }
         }
+        filled_indexes
     }
-
-    
 }
 
-
+/// Beginning of `html` of at most `max_length` bytes, cut between 2 characters
+fn truncate(html: &str, max_length: usize) -> &str {
+    let mut length = max_length.min(html.len());
+    while !html.is_char_boundary(length) {
+        length -= 1;
+    }
+    &html[..length]
+}
 
 #[cfg(test)]
 mod test_selectors {
     use super::*;
+    use crate::css_selector;
+    use crate::parser_options::Whitespace;
+    use matcher_html_selector::MatcherHtmlSelector;
+    use tag_name_html_selector::TagNameHtmlSelector;
+    use tag_path_html_selector::TagPathHtmlSelector;
 
     #[test]
     fn should_format_css_request() {
@@ -81,4 +142,57 @@ mod test_selectors {
 
         assert_eq!(vec![" body", " div p"], actual_css_requests);
     }
+
+    #[test]
+    fn should_read_the_html_the_same_way_in_all_selectors() {
+        let html = "<html><body><div>\n  <p id=a>Caf&eacute;\n  au   lait</p>\n  \
+            <P id=b>x<br>y</P>\n</div></body></html>";
+        let options = ParserOptions::new()
+            .decode_character_references(true)
+            .whitespace(Whitespace::Collapse)
+            .max_content_length(Some(16));
+
+        let founds = TagNameHtmlSelector::default()
+            .options(options)
+            .find_first(html, &["div p"]);
+        assert_eq!(vec!["Café au".to_string()], founds);
+        let founds = MatcherHtmlSelector::default()
+            .options(options)
+            .find_first(html, &[css_selector!(#a)]);
+        assert_eq!(vec!["Café au".to_string()], founds);
+        let matcher = vec![css_selector!(div), css_selector!(p)];
+        let founds = TagPathHtmlSelector::default()
+            .options(options)
+            .find_first(html, &[&matcher]);
+        assert_eq!(vec!["Café au".to_string()], founds);
+
+        // without void elements, `<br>` contains the end of the paragraph
+        let options = options.void_elements(false);
+        let counts = TagNameHtmlSelector::default()
+            .options(options)
+            .count(html, &["p br"]);
+        assert_eq!(vec![1], counts);
+        let matcher = vec![css_selector!(p), css_selector!(br)];
+        let counts = TagPathHtmlSelector::default()
+            .options(options)
+            .count(html, &[&matcher]);
+        assert_eq!(vec![1], counts);
+    }
+
+    #[test]
+    fn should_keep_the_case_of_the_foreign_content_in_all_selectors() {
+        let html = "<DIV><SVG><linearGradient ID=g/></SVG></DIV>";
+
+        let css_requests = ["svg linearGradient", "svg lineargradient"];
+        let counts = TagNameHtmlSelector::default().count(html, &css_requests);
+        assert_eq!(vec![1, 0], counts);
+        let counts = MatcherHtmlSelector::default().count(html, &[css_selector!(linearGradient)]);
+        assert_eq!(vec![1], counts);
+        let counts = MatcherHtmlSelector::default().count(html, &[css_selector!(lineargradient)]);
+        assert_eq!(vec![0], counts);
+        let matcher = vec![css_selector!(div), css_selector!(svg), css_selector!(linearGradient)];
+        let lowercase_matcher = vec![css_selector!(svg), css_selector!(lineargradient)];
+        let counts = TagPathHtmlSelector::default().count(html, &[&matcher, &lowercase_matcher]);
+        assert_eq!(vec![1, 0], counts);
+    }
 }
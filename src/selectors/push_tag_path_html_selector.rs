@@ -0,0 +1,322 @@
+use std::io;
+
+use crate::html_buffer::HtmlBuffer;
+use crate::parse_error::ParseError;
+use crate::parser_options::ParserOptions;
+use crate::selectors::tag_path_html_selector::TagPathHtmlSelector;
+use crate::selectors::FindFirstTextStore;
+use crate::tag_iterator::Token;
+use crate::tag_path::{EndTagRecovery, TagPathItem};
+use crate::utf8_decoder::InvalidUtf8;
+
+type Predicate = dyn Fn(&TagPathItem) -> bool;
+
+/// Event emitted by the push selector as soon as it is known
+#[derive(PartialEq, Debug)]
+pub enum SelectorEvent {
+    /// A start tag matches the matcher at the given index
+    Matched(usize),
+    /// Content of the 1st element matching the matcher at the given index
+    Found(usize, String),
+}
+
+/// Tag path selector fed with chunks of HTML code as they arrive. The tag path and the content of
+/// the matching elements are kept between 2 chunks, and the matches are returned as soon as they
+/// are known.
+///
+/// With the options built with `strict(true)`, the reading stops at the first HTML code that can
+/// not be read as an element. The error is available with `error`.
+pub struct PushTagPathHtmlSelector<'a> {
+    matchers: &'a [&'a Vec<Box<Predicate>>],
+    buffer: HtmlBuffer,
+    html_selector: TagPathHtmlSelector,
+    text_store: FindFirstTextStore,
+    counts: Vec<usize>,
+    founds: Vec<String>,
+    error: Option<ParseError>,
+}
+
+impl<'a> PushTagPathHtmlSelector<'a> {
+    pub fn new(matchers: &'a [&'a Vec<Box<Predicate>>]) -> Self {
+        PushTagPathHtmlSelector {
+            matchers,
+            buffer: HtmlBuffer::new(),
+            html_selector: TagPathHtmlSelector::default(),
+            text_store: FindFirstTextStore::new(matchers.len()),
+            counts: vec![0; matchers.len()],
+            founds: vec![String::new(); matchers.len()],
+            error: None,
+        }
+    }
+
+    /// Read the HTML code with `options`, like the white space policy of the contents found, the
+    /// case folding of the names or the XML mode
+    pub fn options(mut self, options: ParserOptions) -> Self {
+        self.buffer = self.buffer.options(options);
+        self.html_selector = self.html_selector.options(options);
+        self.text_store = FindFirstTextStore::new(self.matchers.len()).options(options);
+        self
+    }
+
+    /// Read the bytes given to `feed_bytes` that are not valid UTF-8 with the given policy. The
+    /// positions of the elements are then positions into the decoded HTML code.
+    pub fn invalid_utf8(mut self, invalid_utf8: InvalidUtf8) -> Self {
+        self.buffer = self.buffer.invalid_utf8(invalid_utf8);
+        self
+    }
+
+    /// Read a chunk of HTML code. It returns the events of all the elements completed by the chunk.
+    pub fn feed(&mut self, html: &str) -> Vec<SelectorEvent> {
+        self.buffer.push_str(html);
+        self.read_elements(false)
+    }
+
+    /// Read a chunk of UTF-8 encoded HTML code. A character split between 2 chunks is read with
+    /// the next chunk. It fails on invalid UTF-8, unless another policy is given to
+    /// `invalid_utf8`.
+    pub fn feed_bytes(&mut self, html: &[u8]) -> io::Result<Vec<SelectorEvent>> {
+        self.buffer.push_bytes(html)?;
+        let events = self.read_elements(false);
+        self.check_error()?;
+        Ok(events)
+    }
+
+    /// Read the end of the HTML code. It returns the events of the remaining elements.
+    pub fn finish(&mut self) -> Vec<SelectorEvent> {
+        self.read_elements(true)
+    }
+
+    /// Same as `finish` for the chunks given to `feed_bytes`. A character not completed by the
+    /// last chunk is an invalid UTF-8 sequence.
+    pub fn finish_bytes(&mut self) -> io::Result<Vec<SelectorEvent>> {
+        self.buffer.finish_bytes()?;
+        let events = self.finish();
+        self.check_error()?;
+        Ok(events)
+    }
+
+    /// Count of the matching of each matcher so far
+    pub fn counts(&self) -> &[usize] {
+        &self.counts
+    }
+
+    /// Content of the 1st element matching each matcher so far. It is empty until the content is
+    /// found.
+    pub fn founds(&self) -> &[String] {
+        &self.founds
+    }
+
+    /// Error that stopped the reading, if any
+    pub fn error(&self) -> Option<&ParseError> {
+        self.error.as_ref()
+    }
+
+    /// Fails, as an `io::ErrorKind::InvalidData` error, if the reading was stopped by an error
+    fn check_error(&self) -> io::Result<()> {
+        match &self.error {
+            Some(error) => Err(io::Error::new(io::ErrorKind::InvalidData, error.clone())),
+            None => Ok(()),
+        }
+    }
+
+    /// End tags that did not close the current element so far, with their position in the HTML
+    /// code
+    pub fn end_tag_recoveries(&self) -> &[(usize, EndTagRecovery)] {
+        self.html_selector.end_tag_recoveries()
+    }
+
+    fn read_elements(&mut self, is_last_chunk: bool) -> Vec<SelectorEvent> {
+        let mut events = vec![];
+        if self.error.is_some() {
+            return events;
+        }
+        loop {
+            let (token, html) = match self.buffer.next_token_html(is_last_chunk) {
+                Ok(Some(token)) => token,
+                Ok(None) => break,
+                Err(error) => {
+                    self.error = Some(error);
+                    break;
+                }
+            };
+            match token {
+                Token::Start(tag, _begin, _end) => {
+                    if self.html_selector.has_implied_end_tags(&tag) {
+                        for index in self.text_store.update_content(&mut self.founds) {
+                            events.push(SelectorEvent::Found(index, self.founds[index].clone()));
+                        }
+                    }
+                    self.text_store.push_element_html(html);
+                    let matching = self.html_selector.read_start_tag(tag, self.matchers);
+                    for (index, does_match) in matching.into_iter().enumerate() {
+                        if does_match {
+                            self.counts[index] += 1;
+                            self.text_store.store_starting_position(index);
+                            events.push(SelectorEvent::Matched(index));
+                        }
+                    }
+                }
+                Token::End(tag_name, begin, _end)
+                    if !self.html_selector.is_ignored_end_tag(&tag_name) =>
+                {
+                    self.html_selector.read_end_tag(&tag_name, begin);
+                    for index in self.text_store.update_content(&mut self.founds) {
+                        events.push(SelectorEvent::Found(index, self.founds[index].clone()));
+                    }
+                    self.text_store.push_element_html(html);
+                }
+                token => self.text_store.push_content_html(&token, html),
+            }
+        }
+        events
+    }
+}
+
+#[macro_use]
+#[cfg(test)]
+mod test_push_tag_path_html_selector {
+
+    use crate::css_selector;
+    use crate::parse_error::ParseErrorKind;
+
+    use super::*;
+
+    #[test]
+    fn should_emit_matches_as_soon_as_they_are_known() {
+        let matcher = vec![css_selector!(div), css_selector!(p)];
+        let matchers = vec![&matcher];
+        let mut html_selector = PushTagPathHtmlSelector::new(&matchers);
+
+        assert!(html_selector.feed("<div><").is_empty());
+        assert_eq!(vec![SelectorEvent::Matched(0)], html_selector.feed("p>hel"));
+        assert!(html_selector.feed("lo").is_empty());
+        assert_eq!(
+            vec![
+                SelectorEvent::Found(0, "hello".to_string()),
+                SelectorEvent::Matched(0)
+            ],
+            html_selector.feed("</p><p>world")
+        );
+        // the content was already found
+        assert!(html_selector.finish().is_empty());
+        assert_eq!(&[2], html_selector.counts());
+        assert_eq!(&["hello".to_string()], html_selector.founds());
+    }
+
+    #[test]
+    fn should_stop_at_an_unterminated_tag() {
+        let matcher = vec![css_selector!(p)];
+        let matchers = vec![&matcher];
+        let mut html_selector =
+            PushTagPathHtmlSelector::new(&matchers).options(ParserOptions::new().strict(true));
+
+        assert_eq!(
+            vec![SelectorEvent::Matched(0)],
+            html_selector.feed("<p>foo</p")
+        );
+        assert!(html_selector.feed_bytes(b"\n").is_ok());
+        let error = html_selector.finish_bytes().unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidData, error.kind());
+
+        let error = html_selector.error().expect("a parse error");
+        assert_eq!(ParseErrorKind::UnterminatedTag, error.kind);
+        assert_eq!((6, 1, 7), (error.offset, error.line, error.column));
+        // nothing is read after the error
+        assert!(html_selector.feed("<p>bar</p>").is_empty());
+        assert_eq!(&[1], html_selector.counts());
+    }
+
+    #[test]
+    fn should_read_invalid_utf8_as_windows_1252_characters() {
+        let matcher = vec![css_selector!(p)];
+        let matchers = vec![&matcher];
+        let mut html_selector =
+            PushTagPathHtmlSelector::new(&matchers).invalid_utf8(InvalidUtf8::Windows1252);
+
+        let mut events = html_selector.feed_bytes(b"<p>caf\xe9 \x80\xe2").unwrap();
+        events.extend(html_selector.feed_bytes(b"\x82\xac</p>").unwrap());
+        events.extend(html_selector.finish_bytes().unwrap());
+
+        assert_eq!(
+            vec![
+                SelectorEvent::Matched(0),
+                SelectorEvent::Found(0, "café €€".to_string())
+            ],
+            events
+        );
+    }
+
+    #[test]
+    fn should_read_an_xml_feed_split_between_chunks() {
+        let xml = "<rss><item><link>https://a.example</link>\
+                   <title><![CDATA[a > b</b>]]></title></item></rss>";
+        let link_matcher = vec![css_selector!(item), css_selector!(link)];
+        let title_matcher = vec![css_selector!(item), css_selector!(title)];
+        let matchers = vec![&link_matcher, &title_matcher];
+
+        for chunk_size in 1..xml.len() {
+            let mut html_selector =
+                PushTagPathHtmlSelector::new(&matchers).options(ParserOptions::new().xml(true));
+            for chunk in xml.as_bytes().chunks(chunk_size) {
+                html_selector.feed_bytes(chunk).unwrap();
+            }
+            html_selector.finish_bytes().unwrap();
+            assert_eq!(
+                &["https://a.example".to_string(), "a > b</b>".to_string()],
+                html_selector.founds(),
+                "chunk size {}",
+                chunk_size
+            );
+        }
+    }
+
+    #[test]
+    fn should_decode_the_attribute_values_with_the_options() {
+        let matcher = vec![css_selector!(a[href = "?a=1&b=2"])];
+        let matchers = vec![&matcher];
+        let options = ParserOptions::new().decode_character_references(true);
+        let mut html_selector = PushTagPathHtmlSelector::new(&matchers).options(options);
+
+        html_selector.feed("<a href='?a=1&am");
+        html_selector.feed("p;b=2'>caf&eacute;</a>");
+        html_selector.finish();
+
+        assert_eq!(&[1], html_selector.counts());
+        assert_eq!(&["café".to_string()], html_selector.founds());
+    }
+
+    #[test]
+    fn should_find_the_same_content_than_find_first_given_small_chunks() {
+        // #costBreakdown > tbody > tr:nth-child(9) > td:nth-child(2) > strong
+        let html = std::fs::read_to_string("./amazon_command.html").unwrap();
+        let total_matcher = vec![
+            css_selector!(#costBreakdown),
+            css_selector!(tbody),
+            css_selector!(tr:nth-child(9)),
+            css_selector!(td:nth-child(2)),
+            css_selector!(strong),
+        ];
+        let matchers = vec![&total_matcher];
+        let mut html_selector = PushTagPathHtmlSelector::new(&matchers);
+
+        let mut events = vec![];
+        let mut chunk = String::new();
+        for c in html.chars() {
+            chunk.push(c);
+            if chunk.len() >= 100 {
+                events.extend(html_selector.feed(&chunk));
+                chunk.clear();
+            }
+        }
+        events.extend(html_selector.feed(&chunk));
+        events.extend(html_selector.finish());
+
+        assert_eq!(
+            vec![
+                SelectorEvent::Matched(0),
+                SelectorEvent::Found(0, "EUR 61,90".to_string())
+            ],
+            events
+        );
+    }
+}
@@ -1,9 +1,25 @@
 use crate::tag_path::TagPathItem;
 
+type Predicate = dyn Fn(&TagPathItem) -> bool;
+
 pub fn tag_name_predicate(name: String) -> Box<dyn Fn(&TagPathItem) -> bool> {
     Box::new(move |tag_path_item: &TagPathItem| tag_path_item.tag.name == name)
 }
 
+/// Match a tag by its name without namespace prefix, like `rect` for `<rect>` and `<svg:rect>`
+pub fn local_name_predicate(local_name: String) -> Box<dyn Fn(&TagPathItem) -> bool> {
+    Box::new(move |tag_path_item: &TagPathItem| tag_path_item.tag.local_name() == local_name)
+}
+
+/// Match a tag in the namespace, like `svg` for the elements inside an `<svg>`, or a tag written
+/// with the namespace prefix, like `<svg:rect>`
+pub fn namespace_predicate(prefix: String) -> Box<dyn Fn(&TagPathItem) -> bool> {
+    Box::new(move |tag_path_item: &TagPathItem| {
+        tag_path_item.namespace.prefix() == prefix
+            || tag_path_item.tag.prefix() == Some(prefix.as_str())
+    })
+}
+
 pub fn id_predicate(id: String) -> Box<dyn Fn(&TagPathItem) -> bool> {
     Box::new(move |tag_path_item: &TagPathItem| {
         if let Some(actual_id) = tag_path_item.tag.id() {
@@ -16,86 +32,119 @@ pub fn id_predicate(id: String) -> Box<dyn Fn(&TagPathItem) -> bool> {
 pub fn class_predicate(class: String) -> Box<dyn Fn(&TagPathItem) -> bool> {
     Box::new(move |tag_path_item: &TagPathItem| {
         if let Some(actual_classes) = tag_path_item.tag.classes() {
-            return actual_classes.contains(class.as_str());
+            return has_word(actual_classes, &class);
         }
         false
     })
 }
 
+/// Match a tag having the attribute, with or without value, like `[hidden]`
 pub fn has_attribute_predicate(attribute_name: String) -> Box<dyn Fn(&TagPathItem) -> bool> {
     Box::new(move |tag_path_item: &TagPathItem| {
-        tag_path_item.tag.attributes.get(&attribute_name).is_some()
+        tag_path_item.tag.attributes.contains_key(&attribute_name)
     })
 }
 
+/// Match a tag having the attribute with exactly the value, like `[type="text"]`. An attribute
+/// without value, like `hidden`, has an empty value.
 pub fn attribute_equals_predicate(
     attribute_name: String,
     attribute_value: String,
 ) -> Box<dyn Fn(&TagPathItem) -> bool> {
     Box::new(move |tag_path_item: &TagPathItem| {
-        if let Some(actual_attribute_value) = tag_path_item.tag.attributes.get(&attribute_name) {
-            return actual_attribute_value.eq(&attribute_value);
-        }
-        false
+        tag_path_item.tag.attributes.get(&attribute_name) == Some(attribute_value.as_str())
     })
 }
 
+/// Match a tag having the attribute with the value, ignoring the case of the ASCII letters, like
+/// `[type="text" i]`
+pub fn attribute_equals_ignore_case_predicate(
+    attribute_name: String,
+    attribute_value: String,
+) -> Box<dyn Fn(&TagPathItem) -> bool> {
+    Box::new(move |tag_path_item: &TagPathItem| {
+        tag_path_item
+            .tag
+            .attributes
+            .get(&attribute_name)
+            .is_some_and(|actual_attribute_value| {
+                actual_attribute_value.eq_ignore_ascii_case(&attribute_value)
+            })
+    })
+}
+
+/// Match a tag having the attribute starting with the value, like `[href^="https"]`. An empty
+/// value matches nothing.
 pub fn attribute_starts_with_predicate(
     attribute_name: String,
     attribute_value: String,
 ) -> Box<dyn Fn(&TagPathItem) -> bool> {
     Box::new(move |tag_path_item: &TagPathItem| {
         if let Some(actual_attribute_value) = tag_path_item.tag.attributes.get(&attribute_name) {
-            return actual_attribute_value.starts_with(&attribute_value);
+            return !attribute_value.is_empty()
+                && actual_attribute_value.starts_with(&attribute_value);
         }
         false
     })
 }
 
+/// Match a tag having the attribute ending with the value, like `[href$=".pdf"]`. An empty value
+/// matches nothing.
 pub fn attribute_ends_with_predicate(
     attribute_name: String,
     attribute_value: String,
 ) -> Box<dyn Fn(&TagPathItem) -> bool> {
     Box::new(move |tag_path_item: &TagPathItem| {
         if let Some(actual_attribute_value) = tag_path_item.tag.attributes.get(&attribute_name) {
-            return actual_attribute_value.ends_with(&attribute_value);
+            return !attribute_value.is_empty()
+                && actual_attribute_value.ends_with(&attribute_value);
         }
         false
     })
 }
 
+/// Match a tag having the attribute containing the value, like `[title*="foo"]`. An empty value
+/// matches nothing.
 pub fn attribute_contains_with_predicate(
     attribute_name: String,
     attribute_value: String,
 ) -> Box<dyn Fn(&TagPathItem) -> bool> {
     Box::new(move |tag_path_item: &TagPathItem| {
         if let Some(actual_attribute_value) = tag_path_item.tag.attributes.get(&attribute_name) {
-            return actual_attribute_value.contains(&attribute_value);
+            return !attribute_value.is_empty()
+                && actual_attribute_value.contains(&attribute_value);
         }
         false
     })
 }
+
+/// Match a tag having the attribute containing the word in its white space separated words, like
+/// `[rel~="nofollow"]`.
 pub fn attribute_has_word_predicate(
     attribute_name: String,
     word: String,
 ) -> Box<dyn Fn(&TagPathItem) -> bool> {
     Box::new(move |tag_path_item: &TagPathItem| {
         if let Some(actual_attribute_value) = tag_path_item.tag.attributes.get(&attribute_name) {
-            return actual_attribute_value.starts_with(&format!("{} ", &word))
-                || actual_attribute_value.contains(&format!(" {} ", &word))
-                || actual_attribute_value.ends_with(&format!(" {}", &word));
+            return has_word(actual_attribute_value, &word);
         }
         false
     })
 }
 
+/// Returns true if `word` is one of the white space separated words of `value`. An empty word or
+/// a word with white spaces is never found.
+fn has_word(value: &str, word: &str) -> bool {
+    value.split_ascii_whitespace().any(|value_word| value_word == word)
+}
+
 pub fn and_predicate(
-    predicates: Vec<Box<dyn Fn(&TagPathItem) -> bool>>,
+    predicates: Vec<Box<Predicate>>,
 ) -> Box<dyn Fn(&TagPathItem) -> bool> {
     Box::new(move |tag_path_item: &TagPathItem| {
         predicates
             .iter()
-            .all(|predicate| predicate(&tag_path_item))
+            .all(|predicate| predicate(tag_path_item))
     })
 }
 
@@ -108,12 +157,15 @@ mod test_selectors {
 
     use super::*;
     use crate::elements::{start_element::Tag, Element};
+    use crate::tag_path::Namespace;
 
     fn create_tag(html: &str) -> TagPathItem {
         let tag = Tag::extract(html).expect("invalid code to create tag for test");
+        let namespace = Namespace::of_element(None, &tag.name);
         TagPathItem {
             tag: Box::new(tag),
             nth_child: 1,
+            namespace,
         }
     }
 
@@ -181,6 +233,16 @@ mod test_selectors {
         assert!(does_match);
     }
     #[test]
+    fn should_match_an_attribute_value_ignoring_case_only_when_asked() {
+        let tag_path_item = create_tag("<input type='Text'>");
+
+        let matcher = attribute_equals_predicate(String::from("type"), String::from("text"));
+        assert!(!matcher(&tag_path_item));
+        let matcher =
+            attribute_equals_ignore_case_predicate(String::from("type"), String::from("text"));
+        assert!(matcher(&tag_path_item));
+    }
+    #[test]
     fn should_match_a_tag_with_attribute_starts_with_its_value() {
         let tag_path_item = create_tag("<div foo='baaaaar'>");
 
@@ -225,6 +287,48 @@ mod test_selectors {
         assert!(does_match);
     }
 
+    #[test]
+    fn should_match_a_tag_with_attribute_without_value_or_with_empty_value() {
+        let matcher = has_attribute_predicate(String::from("data-x"));
+        assert!(matcher(&create_tag("<div data-x>")));
+        assert!(matcher(&create_tag("<div data-x=''>")));
+        assert!(!matcher(&create_tag("<div>")));
+    }
+    #[test]
+    fn should_match_an_attribute_without_value_as_an_empty_value() {
+        let tag_path_item = create_tag("<div hidden>");
+
+        let matcher = attribute_equals_predicate(String::from("hidden"), String::new());
+        assert!(matcher(&tag_path_item));
+        let matcher = attribute_equals_predicate(String::from("hidden"), String::from("true"));
+        assert!(!matcher(&tag_path_item));
+        let matcher = attribute_equals_predicate(String::from("hidden"), String::from("true"));
+        assert!(matcher(&create_tag("<div hidden='true'>")));
+    }
+    #[test]
+    fn should_not_match_an_empty_value_with_substring_predicates() {
+        let tag_path_item = create_tag("<div foo='bar'>");
+
+        let matcher = attribute_starts_with_predicate(String::from("foo"), String::new());
+        assert!(!matcher(&tag_path_item));
+        let matcher = attribute_ends_with_predicate(String::from("foo"), String::new());
+        assert!(!matcher(&tag_path_item));
+        let matcher = attribute_contains_with_predicate(String::from("foo"), String::new());
+        assert!(!matcher(&tag_path_item));
+        let matcher = attribute_has_word_predicate(String::from("foo"), String::new());
+        assert!(!matcher(&tag_path_item));
+    }
+    #[test]
+    fn should_match_whole_words_only() {
+        let matcher = attribute_has_word_predicate(String::from("data"), String::from("foo"));
+        assert!(matcher(&create_tag("<div data='foo'>")));
+        assert!(!matcher(&create_tag("<div data='foobar baz'>")));
+
+        let matcher = class_predicate(String::from("foo"));
+        assert!(matcher(&create_tag("<div class='bar\tfoo'>")));
+        assert!(!matcher(&create_tag("<div class='foobar'>")));
+    }
+
     // X:nth-child(n)
     #[test]
     fn should_match_the_second_nth_child() {
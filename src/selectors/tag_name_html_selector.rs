@@ -1,13 +1,17 @@
-use crate::elements::start_element::Tag;
+use crate::elements::start_element::TagRef;
+use crate::parser_options::ParserOptions;
 use crate::selectors::format_css_request;
-use crate::tag_iterator::{Elements, TagIterator};
+use crate::tag_iterator::{TagIterator, Token, TokenSource};
+use crate::tag_path::{EndTagRecovery, OpenElements};
 
 use crate::selectors::HtmlSelectorCounter;
 use crate::selectors::HtmlSelectorFindFirst;
 
 pub struct TagNameHtmlSelector {
-    tag_name_path: Vec<String>,
-    tag_name_path_string: String,
+    /// open elements, whose names separated by a space are matched, like `html body div`
+    open_elements: OpenElements,
+    options: ParserOptions,
+    end_tag_recoveries: Vec<(usize, EndTagRecovery)>,
 }
 
 impl Default for TagNameHtmlSelector {
@@ -19,12 +23,34 @@ impl Default for TagNameHtmlSelector {
 impl TagNameHtmlSelector {
     fn new() -> Self {
         TagNameHtmlSelector {
-            tag_name_path: Vec::new(),
-            tag_name_path_string: String::new(),
+            open_elements: OpenElements::new(),
+            options: ParserOptions::new(),
+            end_tag_recoveries: Vec::new(),
         }
     }
 
-    fn search_for_css(&self, css_requests: &[String], counts: &mut Vec<usize>) {
+    /// Read the HTML code with `options`, like the white space policy of the contents found, the
+    /// case folding of the names or the XML mode
+    pub fn options(mut self, options: ParserOptions) -> Self {
+        self.options = options;
+        self.open_elements = self.open_elements.options(options);
+        self
+    }
+
+    /// End tags that did not close the current element, with their position in the last HTML
+    /// code read : either they closed outer elements too, or they were ignored
+    pub fn end_tag_recoveries(&self) -> &[(usize, EndTagRecovery)] {
+        &self.end_tag_recoveries
+    }
+
+    /// Forget the open elements and the end tag recoveries of the HTML code read before, to read
+    /// a new HTML document
+    fn reset(&mut self) {
+        self.open_elements.clear();
+        self.end_tag_recoveries.clear();
+    }
+
+    fn search_for_css(&self, css_requests: &[String], counts: &mut [usize]) {
         css_requests
             .iter()
             .enumerate()
@@ -44,80 +70,123 @@ impl TagNameHtmlSelector {
     }
 
     fn match_request(&self, request: &str) -> bool {
-        self.tag_name_path_string.ends_with(request)
+        self.open_elements.names_path().ends_with(request)
     }
 
     fn reduce_path(&mut self) {
-        self.tag_name_path.pop();
-        self.tag_name_path_string = self.tag_name_path.join(" ");
+        self.open_elements.pop();
     }
 
-    fn increase_path(&mut self, tag: Tag) {
-        self.tag_name_path.push(tag.name);
-        self.tag_name_path_string = self.tag_name_path.join(" ");
+    /// Close the open element named `tag_name` and the open elements inside it. The end tag is
+    /// read at `position` in the HTML code.
+    fn close_element(&mut self, tag_name: &str, position: usize) {
+        let recovery = self.open_elements.match_end_tag(tag_name);
+        let depth = self.open_elements.depth() - recovery.closed_count();
+        self.open_elements.truncate(depth);
+        if recovery != EndTagRecovery::Matched {
+            self.end_tag_recoveries.push((position, recovery));
+        }
+    }
+
+    /// Returns true if the tag has no end tag : it is written like `<br/>`, or it is a void
+    /// element like `<br>` when the options read the void elements
+    fn is_self_closing(&self, tag: &TagRef<'_>) -> bool {
+        self.open_elements
+            .is_self_closing(tag.name, tag.is_autoclosing)
+    }
+
+    /// Returns true if the end tag is ignored : in HTML, the end tag of a void element, like
+    /// `</br>`, is ignored
+    fn is_ignored_end_tag(&self, tag_name: &str) -> bool {
+        self.open_elements.is_ignored_end_tag(tag_name)
+    }
+
+    /// Count the tags closed by the start tag because their end tag is optional
+    fn count_implied_end_tags(&self, tag: &TagRef<'_>) -> usize {
+        self.open_elements.count_implied_end_tags(tag.name)
+    }
+
+    fn increase_path(&mut self, tag: &TagRef<'_>) {
+        // the start tag may close the last tags, like a `<li>` closes an open `<li>`
+        let depth = self.open_elements.depth() - self.count_implied_end_tags(tag);
+        self.open_elements.truncate(depth);
+        // the names of foreign content keep their case, like `linearGradient` inside an `<svg>`
+        let namespace = self.open_elements.namespace_of(tag.name);
+        self.open_elements.push(tag.name, namespace, ());
     }
 }
 
-impl HtmlSelectorCounter<&str> for TagNameHtmlSelector {
-    fn count(&mut self, html: &str, css_requests: &[&str]) -> Vec<usize> {
+impl TagNameHtmlSelector {
+    /// Count the matching of each CSS request with the elements read from `elements`
+    pub(crate) fn count_elements<S: TokenSource>(
+        &mut self,
+        mut elements: S,
+        css_requests: &[&str],
+    ) -> Vec<usize> {
+        self.reset();
         let css_requests = format_css_request(css_requests);
         let mut counts = vec![0; css_requests.len()];
 
-        let tag_iterator = TagIterator::new(html);
-        tag_iterator.for_each(|element| {
-
-            match element {
-                Elements::Start(tag, _begin, _end) => {
-                    let is_autoclosing = tag.is_autoclosing;
-                    self.increase_path(tag);
+        while let Some((token, _html)) = elements.next_token_html() {
+            match token {
+                Token::Start(tag, _begin, _end) => {
+                    let is_self_closing = self.is_self_closing(&tag);
+                    self.increase_path(&tag);
                     self.search_for_css(&css_requests, &mut counts);
-                    if is_autoclosing {
+                    if is_self_closing {
                         self.reduce_path();
                     }
                 }
-                Elements::End(_, _, _) => {
-                    self.reduce_path();
+                // the end tag of a void element, like `</br>`, is ignored
+                Token::End(tag_name, begin, _) if !self.is_ignored_end_tag(&tag_name) => {
+                    self.close_element(&tag_name, begin);
                 }
                 _ => {}
             }
-        });
+        }
 
         counts
     }
-}
 
-impl HtmlSelectorFindFirst<&str> for TagNameHtmlSelector {
-    fn find_first(&mut self, html: &str, css_requests: &[&str]) -> Vec<String> {
+    /// Returns the content of the 1st element matching each CSS request, read from `elements`
+    pub(crate) fn find_first_elements<S: TokenSource>(
+        &mut self,
+        mut elements: S,
+        css_requests: &[&str],
+    ) -> Vec<String> {
+        self.reset();
         let css_requests = format_css_request(css_requests);
-        
-        let mut founds = vec![String::new(); css_requests.len()];
 
-        let mut text_store = super::FindFirstTextStore::new(css_requests.len());
-        
+        let mut founds = vec![String::new(); css_requests.len()];
 
-        let tag_iterator = TagIterator::new(html);
+        let mut text_store =
+            super::FindFirstTextStore::new(css_requests.len()).options(self.options);
 
-        for element in tag_iterator {
-            match element {
-                Elements::Start(tag, _begin, end) => {
-                    let is_autoclosing_tag = tag.is_autoclosing;
-                    self.increase_path(tag);
+        while let Some((token, html)) = elements.next_token_html() {
+            match token {
+                Token::Start(tag, _begin, _end) => {
+                    if self.count_implied_end_tags(&tag) > 0 {
+                        text_store.update_content(&mut founds);
+                    }
+                    text_store.push_element_html(html);
+                    let is_self_closing_tag = self.is_self_closing(&tag);
+                    self.increase_path(&tag);
                     if let Some(index) = self.does_match_css_request(&css_requests) {
-                        // get begin and end position of the tag in the
+                        // store the content after the start tag
                         // then, if the next decrease the path with the ending tag,
-                        // so we have all tag position
-                        text_store.store_starting_position(index, end);
+                        // so we have all the tag content
+                        text_store.store_starting_position(index);
                     }
-                    if is_autoclosing_tag {
+                    if is_self_closing_tag {
                         self.reduce_path();
                     }
                 }
-                Elements::End(_, begin, _end) => {
-                    self.reduce_path();
-                    text_store.update_content(&mut founds, begin, html);
-
+                Token::End(tag_name, begin, _end) if !self.is_ignored_end_tag(&tag_name) => {
+                    self.close_element(&tag_name, begin);
+                    text_store.update_content(&mut founds);
+                    text_store.push_element_html(html);
                 }
-                _ => {}
+                token => text_store.push_content_html(&token, html),
             }
         }
 
@@ -125,6 +194,18 @@ impl HtmlSelectorFindFirst<&str> for TagNameHtmlSelector {
     }
 }
 
+impl HtmlSelectorCounter<&str> for TagNameHtmlSelector {
+    fn count(&mut self, html: &str, css_requests: &[&str]) -> Vec<usize> {
+        self.count_elements(TagIterator::new(html).options(self.options), css_requests)
+    }
+}
+
+impl HtmlSelectorFindFirst<&str> for TagNameHtmlSelector {
+    fn find_first(&mut self, html: &str, css_requests: &[&str]) -> Vec<String> {
+        self.find_first_elements(TagIterator::new(html).options(self.options), css_requests)
+    }
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -165,6 +246,86 @@ mod tests {
         assert_eq!(count, vec![69]);
     }
 
+    #[test]
+    fn should_not_nest_tags_into_void_elements() {
+        let html =
+            "<html><body><div><br><img src='a.png'></img><p>foo</p></div><p>bar</p></body></html>";
+
+        let css_requests = vec!["div p", "br p", "img p", "body p"];
+
+        let mut html_selector = TagNameHtmlSelector::new();
+
+        let count = html_selector.count(html, &css_requests);
+
+        assert_eq!(count, vec![1, 0, 0, 1]);
+    }
+
+    #[test]
+    fn should_match_tag_names_whatever_their_case() {
+        let html = "<body><TABLE><Tr><td>a</TD><TD>b</td></tR></table></body>";
+
+        let css_requests = vec!["table tr td"];
+
+        let mut html_selector = TagNameHtmlSelector::new();
+        assert_eq!(vec![2], html_selector.count(html, &css_requests));
+
+        let options = ParserOptions::new().fold_case(false);
+        let mut html_selector = TagNameHtmlSelector::new().options(options);
+        assert_eq!(vec![0], html_selector.count(html, &css_requests));
+    }
+
+    #[test]
+    fn should_read_xml_tags_without_html_rules_with_the_xml_options() {
+        let xml = "<feed><link><Entry><p><p>foo</p></p></Entry></link></feed>";
+
+        let css_requests = vec!["link Entry", "p p"];
+
+        let mut html_selector = TagNameHtmlSelector::new();
+        assert_eq!(vec![0, 0], html_selector.count(xml, &css_requests));
+
+        let options = ParserOptions::new().xml(true);
+        let mut html_selector = TagNameHtmlSelector::new().options(options);
+        assert_eq!(vec![1, 1], html_selector.count(xml, &css_requests));
+    }
+
+    #[test]
+    fn should_recover_from_missing_and_stray_end_tags() {
+        let html = "<html><body><div><span>a</div></span><p>b</p></body></html>";
+
+        let css_requests = vec!["body p", "div p", "span p"];
+
+        let mut html_selector = TagNameHtmlSelector::new();
+
+        let count = html_selector.count(html, &css_requests);
+
+        assert_eq!(count, vec![1, 0, 0]);
+        assert_eq!(
+            &[
+                (24, EndTagRecovery::ClosedOpenElements(vec!["span".to_string()])),
+                (30, EndTagRecovery::Ignored)
+            ],
+            html_selector.end_tag_recoveries()
+        );
+    }
+
+    #[test]
+    fn should_read_each_document_from_the_root() {
+        let css_requests = vec!["div span"];
+
+        let mut html_selector = TagNameHtmlSelector::new();
+
+        let unclosed_count = html_selector.count("<html><div><span>a</p>", &css_requests);
+        let count = html_selector.count("<html><span>b</span></html>", &css_requests);
+
+        assert_eq!(unclosed_count, vec![1]);
+        assert_eq!(count, vec![0]);
+        assert!(html_selector.end_tag_recoveries().is_empty());
+        html_selector.count("<html><div><span>a</p>", &css_requests);
+        let founds = html_selector.find_first("<html><span>b</span></html>", &css_requests);
+        assert_eq!(founds, vec![String::new()]);
+        assert!(html_selector.end_tag_recoveries().is_empty());
+    }
+
     #[test]
     fn should_match_many_tag_request() {
         let html = get_html();
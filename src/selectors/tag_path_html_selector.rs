@@ -1,7 +1,11 @@
-use crate::tag_iterator::Elements;
+use crate::elements::start_element::TagRef;
+use crate::parser_options::ParserOptions;
 use crate::tag_iterator::TagIterator;
+use crate::tag_iterator::Token;
+use crate::tag_iterator::TokenSource;
 
 use crate::tag_path::match_tag_path;
+use crate::tag_path::EndTagRecovery;
 use crate::tag_path::TagPath;
 use crate::tag_path::TagPathItem;
 
@@ -9,6 +13,8 @@ type Predicate = dyn Fn(&TagPathItem) -> bool;
 
 pub struct TagPathHtmlSelector {
     path: TagPath,
+    options: ParserOptions,
+    end_tag_recoveries: Vec<(usize, EndTagRecovery)>,
 }
 
 impl Default for TagPathHtmlSelector {
@@ -21,93 +27,154 @@ impl TagPathHtmlSelector {
     fn new() -> Self {
         TagPathHtmlSelector {
             path: TagPath::new(),
+            options: ParserOptions::new(),
+            end_tag_recoveries: Vec::new(),
         }
     }
 
+    /// Read the HTML code with `options`, like the white space policy of the contents found, the
+    /// case folding of the names or the XML mode
+    pub fn options(mut self, options: ParserOptions) -> Self {
+        self.options = options;
+        self.path = TagPath::new().options(options);
+        self
+    }
+
+    /// End tags that did not close the current element, with their position in the last HTML
+    /// code read : either they closed outer elements too, or they were ignored
+    pub fn end_tag_recoveries(&self) -> &[(usize, EndTagRecovery)] {
+        &self.end_tag_recoveries
+    }
+
+    /// Forget the open elements and the end tag recoveries of the HTML code read before, to read
+    /// a new HTML document
+    fn reset(&mut self) {
+        self.path = TagPath::new().options(self.options);
+        self.end_tag_recoveries.clear();
+    }
+
     pub fn count(
         &mut self,
         html: &str,
         matchers: &[&Vec<Box<Predicate>>],
     ) -> Vec<usize> {
-        let mut counts = vec![0; matchers.len()];
+        let tag_iterator = TagIterator::new(html).options(self.options);
+        self.count_elements(tag_iterator, matchers)
+    }
 
-        let tag_iterator = TagIterator::new(html);
-        tag_iterator.for_each(|element| match element {
-            Elements::Start(tag, _begin, _end) => {
-                let is_autoclosing_tag = tag.is_autoclosing;
-                self.path.add(tag);
+    pub fn find_first(
+        &mut self,
+        html: &str,
+        matchers: &[&Vec<Box<Predicate>>],
+    ) -> Vec<String> {
+        let tag_iterator = TagIterator::new(html).options(self.options);
+        self.find_first_elements(tag_iterator, matchers)
+    }
 
-                self.update_counts_if_matching(&mut counts, &matchers);
+    /// Count the matching of each tag path matcher with the elements read from `elements`
+    pub(crate) fn count_elements<S: TokenSource>(
+        &mut self,
+        mut elements: S,
+        matchers: &[&Vec<Box<Predicate>>],
+    ) -> Vec<usize> {
+        self.reset();
+        let mut counts = vec![0; matchers.len()];
 
-                if is_autoclosing_tag {
-                    self.path.reduce()
+        while let Some((token, _html)) = elements.next_token_html() {
+            match token {
+                Token::Start(tag, _begin, _end) => {
+                    self.read_start_tag(tag, matchers)
+                        .into_iter()
+                        .enumerate()
+                        .for_each(|(index, does_match)| {
+                            if does_match {
+                                if let Some(value) = counts.get_mut(index) {
+                                    *value += 1;
+                                }
+                            }
+                        });
                 }
+                Token::End(tag_name, begin, _end) if !self.is_ignored_end_tag(&tag_name) => {
+                    self.read_end_tag(&tag_name, begin);
+                }
+                _ => {}
             }
-            Elements::End(_tag_name, _begin, _end) => {
-                self.path.reduce();
-            }
-            _ => {}
-        });
+        }
 
         counts
     }
 
-    pub fn find_first(
+    /// Returns the content of the 1st element matching each tag path matcher, read from `elements`
+    pub(crate) fn find_first_elements<S: TokenSource>(
         &mut self,
-        html: &str,
+        mut elements: S,
         matchers: &[&Vec<Box<Predicate>>],
     ) -> Vec<String> {
-        
+        self.reset();
         let mut founds = vec![String::new(); matchers.len()];
 
-        let mut text_store = super::FindFirstTextStore::new(matchers.len());
-
-        let tag_iterator = TagIterator::new(html);
-        tag_iterator.for_each(|element| match element {
-            Elements::Start(tag, _begin, end) => {
-                let is_autoclosing_tag = tag.is_autoclosing;
-                self.path.add(tag);
-                self.check_any_matching(&matchers)
-                    .into_iter()
-                    .enumerate()
-                    .for_each(|(index, does_match)| {
-                        if does_match {
-                            text_store.store_starting_position(index, end);
-                        }
-                    });
-                if is_autoclosing_tag {
-                    self.path.reduce();
+        let mut text_store = super::FindFirstTextStore::new(matchers.len()).options(self.options);
+
+        while let Some((token, html)) = elements.next_token_html() {
+            match token {
+                Token::Start(tag, _begin, _end) => {
+                    if self.has_implied_end_tags(&tag) {
+                        text_store.update_content(&mut founds);
+                    }
+                    text_store.push_element_html(html);
+                    self.read_start_tag(tag, matchers)
+                        .into_iter()
+                        .enumerate()
+                        .for_each(|(index, does_match)| {
+                            if does_match {
+                                text_store.store_starting_position(index);
+                            }
+                        });
                 }
+                Token::End(tag_name, begin, _end) if !self.is_ignored_end_tag(&tag_name) => {
+                    self.read_end_tag(&tag_name, begin);
+                    text_store.update_content(&mut founds);
+                    text_store.push_element_html(html);
+                }
+                token => text_store.push_content_html(&token, html),
             }
-            #[cfg(test)]
-            Elements::Text(content) => {
-                println!("\t\t CONTENT : {:?}", content);
-            }
-            Elements::End(_tag_name, begin, _end) => {
-                self.path.reduce();
-                text_store.update_content(&mut founds, begin, html);
-            }
-            _ => {}
-        });
+        }
 
         founds
     }
 
-    fn update_counts_if_matching(
-        &self,
-        counts: &mut Vec<usize>,
+    /// Add a start tag to the tag path. It returns, for each matcher, true if the matcher matches
+    /// the new tag path.
+    pub(crate) fn read_start_tag(
+        &mut self,
+        tag: TagRef<'_>,
         matchers: &[&Vec<Box<Predicate>>],
-    ) {
-        self.check_any_matching(&matchers)
-            .into_iter()
-            .enumerate()
-            .for_each(|(index, does_match)| {
-                if does_match {
-                    if let Some(value) = counts.get_mut(index) {
-                        *value += 1;
-                    }
-                }
-            });
+    ) -> Vec<bool> {
+        self.path.add(tag);
+        let matching = self.check_any_matching(matchers);
+        self.path.close_self_closing_tag();
+        matching
+    }
+
+    /// Returns true if the start tag closes tags whose end tag is optional, like a `<li>` closes
+    /// an open `<li>`
+    pub(crate) fn has_implied_end_tags(&self, tag: &TagRef<'_>) -> bool {
+        self.path.count_implied_end_tags(tag.name) > 0
+    }
+
+    /// Returns true if the end tag is ignored : in HTML, the end tag of a void element, like
+    /// `</br>`, is ignored
+    pub(crate) fn is_ignored_end_tag(&self, tag_name: &str) -> bool {
+        self.path.is_ignored_end_tag(tag_name)
+    }
+
+    /// Close the open element named `tag_name` in the tag path. The end tag is read at `position`
+    /// in the HTML code.
+    pub(crate) fn read_end_tag(&mut self, tag_name: &str, position: usize) {
+        let recovery = self.path.close(tag_name);
+        if recovery != EndTagRecovery::Matched {
+            self.end_tag_recoveries.push((position, recovery));
+        }
     }
 
     fn check_any_matching(
@@ -116,7 +183,7 @@ impl TagPathHtmlSelector {
     ) -> Vec<bool> {
         matchers
             .iter()
-            .map(|matcher| self.check_matching(&matcher))
+            .map(|matcher| self.check_matching(matcher))
             .collect()
     }
 
@@ -171,6 +238,214 @@ mod test_tag_path_html_selector {
         assert_eq!(vec![4, 12], counts);
     }
 
+    #[test]
+    fn should_count_nth_child_after_void_elements() {
+        // GIVEN
+        let html = "<table><tr><td>a<br>b<br></td><td><input type=text></td></tr></table>";
+        let br_matcher = vec![css_selector!(td), css_selector!(br:nth-child(2))];
+        let td_matcher = vec![css_selector!(tr), css_selector!(td:nth-child(2))];
+        let paths_matcher = vec![&br_matcher, &td_matcher];
+        // WHEN
+        let mut html_selector = TagPathHtmlSelector::new();
+        let counts = html_selector.count(html, &paths_matcher);
+        // THEN
+        assert_eq!(vec![1, 1], counts);
+    }
+
+    #[test]
+    fn should_find_cells_of_rows_without_end_tags() {
+        // GIVEN
+        let html = "<table><tr><td>a<td>b<tr><td>c<td>d</table>";
+        let cell_matcher = vec![
+            css_selector!(table),
+            css_selector!(tr:nth-child(2)),
+            css_selector!(td:nth-child(1)),
+        ];
+        let paths_matcher = vec![&cell_matcher];
+        // WHEN
+        let mut html_selector = TagPathHtmlSelector::new();
+        let founds = html_selector.find_first(html, &paths_matcher);
+        // THEN
+        assert_eq!(vec!["c".to_string()], founds);
+    }
+
+    #[test]
+    fn should_recover_from_missing_and_stray_end_tags() {
+        // GIVEN
+        let html = "<div><span>a</div></span><div><p>b</p></div>";
+        let path_matcher = vec![css_selector!(div:nth-child(2)), css_selector!(p)];
+        let paths_matcher = vec![&path_matcher];
+        // WHEN
+        let mut html_selector = TagPathHtmlSelector::new();
+        let counts = html_selector.count(html, &paths_matcher);
+        // THEN
+        assert_eq!(vec![1], counts);
+        assert_eq!(
+            &[
+                (
+                    12,
+                    EndTagRecovery::ClosedOpenElements(vec!["span".to_string()])
+                ),
+                (18, EndTagRecovery::Ignored)
+            ],
+            html_selector.end_tag_recoveries()
+        );
+    }
+
+    #[test]
+    fn should_read_each_document_from_the_root() {
+        // GIVEN
+        let path_matcher = vec![css_selector!(div), css_selector!(p:nth-child(1))];
+        let paths_matcher = vec![&path_matcher];
+        // WHEN
+        let mut html_selector = TagPathHtmlSelector::new();
+        let unclosed_counts = html_selector.count("<div><p>a</p></span>", &paths_matcher);
+        let counts = html_selector.count("<div><p>a</p></div>", &paths_matcher);
+        // THEN
+        assert_eq!(vec![1], unclosed_counts);
+        assert_eq!(vec![1], counts);
+        assert!(html_selector.end_tag_recoveries().is_empty());
+        html_selector.count("<div><p>a</p></span>", &paths_matcher);
+        let founds = html_selector.find_first("<div><p>a</p></div>", &paths_matcher);
+        assert_eq!(vec!["a".to_string()], founds);
+        assert!(html_selector.end_tag_recoveries().is_empty());
+    }
+
+    #[test]
+    fn should_find_a_content_starting_with_a_non_ascii_character() {
+        // GIVEN
+        let html = "<table><tr><td>Total</td><td>€ 61,90</td></tr><tr><td>été</td></tr></table>";
+        let total_matcher = vec![css_selector!(tr), css_selector!(td:nth-child(2))];
+        let date_matcher = vec![css_selector!(tr:nth-child(2)), css_selector!(td)];
+        let paths_matcher = vec![&total_matcher, &date_matcher];
+        // WHEN
+        let mut html_selector = TagPathHtmlSelector::new();
+        let founds = html_selector.find_first(html, &paths_matcher);
+        // THEN
+        assert_eq!(vec!["€ 61,90".to_string(), "été".to_string()], founds);
+    }
+
+    #[test]
+    fn should_read_an_xml_feed() {
+        // GIVEN
+        let xml = "<?xml version=\"1.0\"?><rss><channel>\
+            <Item><link>https://a.example</link></Item>\
+            <item><br/><link>https://b.example</link><title><![CDATA[Tom & <Jerry>]]></title></item>\
+            </channel></rss>";
+        let link_matcher = vec![css_selector!(item), css_selector!(link)];
+        let title_matcher = vec![css_selector!(item), css_selector!(title)];
+        let paths_matcher = vec![&link_matcher, &title_matcher];
+        // WHEN
+        let mut html_selector = TagPathHtmlSelector::new().options(ParserOptions::new().xml(true));
+        let founds = html_selector.find_first(xml, &paths_matcher);
+        // THEN
+        assert_eq!(
+            vec!["https://b.example".to_string(), "Tom & <Jerry>".to_string()],
+            founds
+        );
+        assert!(html_selector.end_tag_recoveries().is_empty());
+
+        // WHEN the feed is read as HTML, `<link>` is a void element
+        let mut html_selector = TagPathHtmlSelector::new();
+        let founds = html_selector.find_first(xml, &paths_matcher);
+        // THEN
+        assert_ne!("https://b.example", founds[0]);
+        assert_eq!("<![CDATA[Tom & <Jerry>]]>", founds[1]);
+    }
+
+    #[test]
+    fn should_match_names_whatever_their_case_in_html() {
+        // GIVEN
+        let html = "<TABLE><TR><TD VALIGN=Top>foo</td></TR></TABLE>\
+            <svg><linearGradient ID=g></linearGradient></svg>";
+        let td_matcher = vec![css_selector!(table), css_selector!(tr), css_selector!(td)];
+        let valign_matcher = vec![css_selector!(td[valign = "Top"])];
+        let top_matcher = vec![css_selector!(td[valign = "top"])];
+        let top_ignoring_case_matcher = vec![css_selector!(td[valign = "top" i])];
+        let gradient_matcher = vec![css_selector!(linearGradient[ID = "g"])];
+        let paths_matcher = vec![
+            &td_matcher,
+            &valign_matcher,
+            &top_matcher,
+            &top_ignoring_case_matcher,
+            &gradient_matcher,
+        ];
+        // WHEN
+        let mut html_selector = TagPathHtmlSelector::new();
+        let counts = html_selector.count(html, &paths_matcher);
+        // THEN the values and the names of foreign content keep their case
+        assert_eq!(vec![1, 1, 0, 1, 1], counts);
+        assert_eq!(
+            "foo",
+            TagPathHtmlSelector::new().find_first(html, &paths_matcher)[0]
+        );
+
+        // WHEN
+        let options = ParserOptions::new().fold_case(false);
+        let mut html_selector = TagPathHtmlSelector::new().options(options);
+        let counts = html_selector.count(html, &paths_matcher);
+        // THEN
+        assert_eq!(vec![0, 0, 0, 0, 1], counts);
+    }
+
+    #[test]
+    fn should_fold_the_names_before_counting_the_siblings_and_reading_the_namespace() {
+        // GIVEN
+        let html = "<table><TR><TD>a</TD></TR><TR><TD>b</TD></TR></table><SVG></SVG>";
+        let second_row_matcher = vec![css_selector!(tr:nth-child(2)), css_selector!(td)];
+        let svg_matcher = vec![css_selector!(svg | svg)];
+        let paths_matcher = vec![&second_row_matcher, &svg_matcher];
+        // WHEN
+        let mut html_selector = TagPathHtmlSelector::new();
+        let counts = html_selector.count(html, &paths_matcher);
+        let founds = html_selector.find_first(html, &paths_matcher);
+        // THEN
+        assert_eq!(vec![1, 1], counts);
+        assert_eq!("b", founds[0]);
+    }
+
+    #[test]
+    fn should_select_inline_svg_elements_by_namespace() {
+        // GIVEN
+        let html = r##"<div><svg><defs><linearGradient id="g"><stop/></linearGradient></defs>
+            <path d="M0"/><use xlink:href="#g"/><text>Total</text>
+            <foreignObject><title>html title</title></foreignObject></svg>
+            <title>not svg</title></div>"##;
+        let gradient_matcher = vec![css_selector!(svg | linearGradient)];
+        let text_matcher = vec![css_selector!(svg), css_selector!(svg | text)];
+        let title_matcher = vec![css_selector!(svg | title)];
+        let html_title_matcher = vec![css_selector!(html | title)];
+        let use_matcher = vec![css_selector!(*|use)];
+        let paths_matcher = vec![
+            &gradient_matcher,
+            &text_matcher,
+            &title_matcher,
+            &html_title_matcher,
+            &use_matcher,
+        ];
+        // WHEN
+        let mut html_selector = TagPathHtmlSelector::new();
+        let counts = html_selector.count(html, &paths_matcher);
+        let founds = html_selector.find_first(html, &paths_matcher);
+        // THEN
+        assert_eq!(vec![1, 1, 0, 2, 1], counts);
+        assert_eq!("Total", founds[1]);
+        assert_eq!("html title", founds[3]);
+    }
+
+    #[test]
+    fn should_not_read_tags_inside_a_script() {
+        // GIVEN
+        let html = "<div><script>if (a<b) { s = '</div><p>'; }</script><p>foo</p></div>";
+        let path_matcher = vec![css_selector!(div), css_selector!(p)];
+        let paths_matcher = vec![&path_matcher];
+        // WHEN
+        let mut html_selector = TagPathHtmlSelector::new();
+        let founds = html_selector.find_first(html, &paths_matcher);
+        // THEN
+        assert_eq!(vec!["foo".to_string()], founds);
+    }
+
     #[test]
     fn should_get_label_in_deep_dom_tree() {
         // GIVEN
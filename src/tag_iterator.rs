@@ -1,31 +1,244 @@
+use std::borrow::Cow;
+
+use crate::character_reference::{decode_attribute_value, decode_character_references};
 use crate::elements::{
-    comment_element::CommentElement, doctype_element::DoctypeElement, end_element::EndElement,
-    start_element::Tag, text_element::TextElement, Element,
+    attributes::Attributes,
+    cdata_element::CdataElement,
+    comment_element::CommentElement,
+    doctype_element::DoctypeElement,
+    end_element::EndElement,
+    processing_instruction_element::ProcessingInstructionElement,
+    start_element::{find_tag_end, Tag, TagEndSearch, TagRef},
+    text_element::TextElement,
+    Element,
 };
+use crate::parse_error::{ParseError, ParseErrorKind};
+use crate::parser_options::ParserOptions;
+use crate::tag_path::OpenElements;
 
+/// Element of an HTML document, with its span : the position in bytes of its beginning and of
+/// its end into the whole HTML document. New kinds of elements may be added.
 #[derive(PartialEq, Debug)]
+#[non_exhaustive]
 pub enum Elements {
+    /// Start tag, like `<div class="foo">`
     Start(Tag, usize, usize),
+    /// End tag, like `</div>`, with the tag name
     End(String, usize, usize),
-    Comment(String),
-    Text(String),
-    Doctype(String),
+    /// Comment, like `<!-- foo -->`, with the text between `<!--` and `-->`
+    Comment(String, usize, usize),
+    /// Text between 2 tags. A CDATA section, read as a text in XML, gives its content.
+    Text(String, usize, usize),
+    /// Document type declaration, like `<!DOCTYPE html>`, with the text after `<!DOCTYPE`
+    Doctype(String, usize, usize),
+    /// Processing instruction of an XML document, like `<?xml version="1.0"?>`
+    ProcessingInstruction(String, usize, usize),
+}
+
+/// Borrowed version of `Elements` : the content of each token is a slice of the HTML code, so
+/// reading a token does not allocate. New kinds of tokens may be added.
+#[derive(PartialEq, Debug)]
+#[non_exhaustive]
+pub enum Token<'a> {
+    /// Start tag, like `<div class="foo">` : its attributes are parsed when they are read
+    Start(TagRef<'a>, usize, usize),
+    /// End tag, like `</div>`, with the tag name
+    End(Cow<'a, str>, usize, usize),
+    /// Comment, like `<!-- foo -->`, with the text between `<!--` and `-->`
+    Comment(&'a str, usize, usize),
+    /// Text between 2 tags, with its character references not decoded. A CDATA section, read as
+    /// a text in XML, gives its content.
+    Text(&'a str, usize, usize),
+    /// Document type declaration, like `<!DOCTYPE html>`, with the text after `<!DOCTYPE`
+    Doctype(Cow<'a, str>, usize, usize),
+    /// Processing instruction of an XML document, like `<?xml version="1.0"?>`
+    ProcessingInstruction(&'a str, usize, usize),
+}
+
+impl Elements {
+    /// Position in bytes of the beginning and of the end of the element into the HTML document
+    pub fn span(&self) -> (usize, usize) {
+        match self {
+            Elements::Start(_, begin, end)
+            | Elements::End(_, begin, end)
+            | Elements::Comment(_, begin, end)
+            | Elements::Text(_, begin, end)
+            | Elements::Doctype(_, begin, end)
+            | Elements::ProcessingInstruction(_, begin, end) => (*begin, *end),
+        }
+    }
+}
+
+impl Token<'_> {
+    /// Position in bytes of the beginning and of the end of the token into the HTML document
+    pub fn span(&self) -> (usize, usize) {
+        match self {
+            Token::Start(_, begin, end)
+            | Token::End(_, begin, end)
+            | Token::Comment(_, begin, end)
+            | Token::Text(_, begin, end)
+            | Token::Doctype(_, begin, end)
+            | Token::ProcessingInstruction(_, begin, end) => (*begin, *end),
+        }
+    }
+}
+
+impl From<Token<'_>> for Elements {
+    fn from(token: Token<'_>) -> Self {
+        match token {
+            Token::Start(tag, begin, end) => Elements::Start(Tag::from(tag), begin, end),
+            Token::End(name, begin, end) => Elements::End(name.into_owned(), begin, end),
+            Token::Comment(content, begin, end) => {
+                Elements::Comment(content.to_string(), begin, end)
+            }
+            Token::Text(content, begin, end) => Elements::Text(content.to_string(), begin, end),
+            Token::Doctype(content, begin, end) => {
+                Elements::Doctype(content.into_owned(), begin, end)
+            }
+            Token::ProcessingInstruction(content, begin, end) => {
+                Elements::ProcessingInstruction(content.to_string(), begin, end)
+            }
+        }
+    }
 }
 
+/// Reader of the elements of an HTML document, in the order of the HTML code. It iterates over
+/// the owned `Elements`, and `tokens` iterates over the borrowed `Token`s.
 pub struct TagIterator<'a> {
+    /// whole HTML document
+    source: &'a str,
+    /// HTML code not read yet
     html: &'a str,
     reading_position: usize,
+    /// HTML code of the last element returned by the iterator
+    last_element_html: &'a str,
+    /// options like the decoding of the character references of the elements
+    options: ParserOptions,
+    /// name of the element whose content is read as text, like `script`
+    raw_text_element: Option<&'static str>,
+    /// error that stopped the iteration
+    error: Option<ParseError>,
+    /// position into the whole HTML document after which there is no `>`, once known : the
+    /// elements starting with `<` after it can not end, so their end is not searched again
+    no_markup_end_from: Option<usize>,
+    /// elements open around the next element returned by the iterator
+    open_elements: OpenElements,
+    /// depth of the last element returned by the iterator
+    last_element_depth: usize,
 }
 
 impl<'a> TagIterator<'a> {
+    /// Read the elements of `html` with the default options : like a browser does, without
+    /// decoding the character references
     pub fn new(html: &'a str) -> Self {
         TagIterator {
+            source: html,
             html,
             reading_position: 0,
+            last_element_html: "",
+            options: ParserOptions::new(),
+            raw_text_element: None,
+            error: None,
+            no_markup_end_from: None,
+            open_elements: OpenElements::new(),
+            last_element_depth: 0,
         }
     }
+    /// Read the elements with `options`, like the decoding of the character references, the case
+    /// folding of the names of the HTML elements, the XML mode, or the strict mode where the
+    /// iteration stops at the HTML code that can not be read as an element, like `< 3` or
+    /// `<!foo>`. The tokens are never decoded nor folded : they borrow the HTML code.
+    pub fn options(mut self, options: ParserOptions) -> Self {
+        self.options = options;
+        self.open_elements = self.open_elements.options(options);
+        self
+    }
+    /// Read the next token, borrowing the HTML code. It returns `Ok(None)` at the end of the
+    /// HTML code. In strict mode, it fails if the HTML code can not be read as an element.
+    pub fn try_next_token(&mut self) -> Result<Option<Token<'a>>, ParseError> {
+        if self.html.is_empty() {
+            return Ok(None);
+        }
+        let next_token = if is_unterminated_markup(
+            self.html,
+            self.reading_position,
+            self.no_markup_end_from,
+            self.raw_text_element,
+        ) {
+            None
+        } else {
+            extract_next_token(
+                self.html,
+                self.reading_position,
+                self.raw_text_element,
+                self.options.xml,
+            )
+        };
+        let (token, length) = match next_token {
+            Some(token) => token,
+            None if self.options.strict => {
+                let html_before = &self.source[..self.reading_position];
+                return Err(ParseError::new(parse_error_kind(self.html), html_before));
+            }
+            None => {
+                if self.no_markup_end_from.is_none() && !self.html.contains('>') {
+                    self.no_markup_end_from = Some(self.reading_position);
+                }
+                recover_token(self.html, self.reading_position)
+            }
+        };
+        self.raw_text_element = raw_text_element(&token).filter(|_| !self.options.xml);
+        self.reduce_html(length);
+        Ok(Some(token))
+    }
+    /// Read the next token, borrowing the HTML code. The iteration stops at the first error :
+    /// the error is available with `take_error`.
+    pub fn next_token(&mut self) -> Option<Token<'a>> {
+        if self.error.is_some() {
+            return None;
+        }
+        self.try_next_token().unwrap_or_else(|error| {
+            self.error = Some(error);
+            None
+        })
+    }
+    /// Iterate over the tokens, borrowing the HTML code
+    pub fn tokens(mut self) -> impl Iterator<Item = Token<'a>> {
+        std::iter::from_fn(move || self.next_token())
+    }
+    /// Iterate over the elements, then over the error that stopped the iteration, if any
+    pub fn try_elements(mut self) -> impl Iterator<Item = Result<Elements, ParseError>> + 'a {
+        let mut is_finished = false;
+        std::iter::from_fn(move || {
+            if is_finished {
+                return None;
+            }
+            match self.next() {
+                Some(element) => Some(Ok(element)),
+                None => {
+                    is_finished = true;
+                    self.take_error().map(Err)
+                }
+            }
+        })
+    }
+    /// Returns the error that stopped the iteration, if any.
+    pub fn take_error(&mut self) -> Option<ParseError> {
+        self.error.take()
+    }
+    /// Iterate over the elements with their depth : the count of the elements open around them.
+    /// An end tag has the depth of its start tag. The elements are closed like the selectors do,
+    /// with the void elements and the implied end tags of the options.
+    pub fn with_depth(self) -> WithDepth<'a> {
+        WithDepth { elements: self }
+    }
+    /// HTML code of the last element returned by the iterator
+    pub(crate) fn last_element_html(&self) -> &'a str {
+        self.last_element_html
+    }
     fn reduce_html(&mut self, element_length: usize) -> usize {
         self.reading_position += element_length;
+        self.last_element_html = self.html.get(..element_length).unwrap_or(self.html);
         let reduced_html = self.html.get(element_length..);
         if let Some(html) = reduced_html {
             self.html = html;
@@ -36,31 +249,422 @@ impl<'a> TagIterator<'a> {
     }
 }
 
+/// Reader of the tokens of an HTML document, that also gives the HTML code of each token. The
+/// selectors read the tokens, so they only allocate for what they keep, like the tags of the tag
+/// path or the contents found, without keeping the whole HTML document.
+pub(crate) trait TokenSource {
+    /// Read the next token with its HTML code. The token borrows the HTML code of the source.
+    fn next_token_html(&mut self) -> Option<(Token<'_>, &str)>;
+}
+
+impl<S: TokenSource> TokenSource for &mut S {
+    fn next_token_html(&mut self) -> Option<(Token<'_>, &str)> {
+        (**self).next_token_html()
+    }
+}
+
+impl TokenSource for TagIterator<'_> {
+    fn next_token_html(&mut self) -> Option<(Token<'_>, &str)> {
+        let token = self.next_token()?;
+        Some((token, self.last_element_html()))
+    }
+}
+
+/// Iterator over the elements with their depth, built by `TagIterator::with_depth`
+pub struct WithDepth<'a> {
+    elements: TagIterator<'a>,
+}
+
+impl WithDepth<'_> {
+    /// Returns the error that stopped the iteration, if any.
+    pub fn take_error(&mut self) -> Option<ParseError> {
+        self.elements.take_error()
+    }
+}
+
+impl Iterator for WithDepth<'_> {
+    type Item = (Elements, usize);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let element = self.elements.next()?;
+        Some((element, self.elements.last_element_depth))
+    }
+}
+
+impl OpenElements {
+    /// Read an element : a start tag opens its element and an end tag closes it, and their names
+    /// are lowercased if the options fold the case of their namespace. It returns the depth of
+    /// the element.
+    pub(crate) fn read(&mut self, element: &mut Elements) -> usize {
+        match element {
+            Elements::Start(tag, _, _) => self.open(tag),
+            Elements::End(tag_name, _, _) => self.close(tag_name),
+            _ => self.depth(),
+        }
+    }
+
+    /// Open the element of a start tag. It returns the depth of the element.
+    fn open(&mut self, tag: &mut Tag) -> usize {
+        self.truncate(self.depth() - self.count_implied_end_tags(&tag.name));
+        let namespace = self.namespace_of(&tag.name);
+        if self.folds_case(namespace, &tag.name) {
+            tag.fold_case();
+        }
+        let depth = self.depth();
+        if !self.is_self_closing(&tag.name, tag.is_autoclosing) {
+            self.push(&tag.name, namespace, ());
+        }
+        depth
+    }
+
+    /// Close the element of an end tag and the elements open inside it. It returns the depth of
+    /// the element. An end tag that matches no open element is ignored.
+    fn close(&mut self, tag_name: &mut str) -> usize {
+        if self.folds_end_tag_case(tag_name) {
+            tag_name.make_ascii_lowercase();
+        }
+        if !self.is_ignored_end_tag(tag_name) {
+            let closed_count = self.match_end_tag(tag_name).closed_count();
+            self.truncate(self.depth() - closed_count);
+        }
+        self.depth()
+    }
+}
+
+/// Decode the character references of the text or of the attribute values of an element
+pub(crate) fn decode_element(element: Elements) -> Elements {
+    match element {
+        Elements::Text(text, begin, end) => match decode_character_references(&text) {
+            Cow::Borrowed(_) => Elements::Text(text, begin, end),
+            Cow::Owned(decoded_text) => Elements::Text(decoded_text, begin, end),
+        },
+        Elements::Start(mut tag, begin, end) => {
+            decode_attributes(&mut tag.attributes);
+            Elements::Start(tag, begin, end)
+        }
+        element => element,
+    }
+}
+
+/// Element of the token read with `options`, whose HTML code is `html` : `None` if the options
+/// skip it, like a text made of white spaces only. Its character references are decoded if the
+/// options ask for it, except in the content of a `raw_text_element` like `script`, and in a
+/// CDATA section.
+pub(crate) fn read_element(
+    token: Token<'_>,
+    html: &str,
+    raw_text_element: Option<&str>,
+    options: ParserOptions,
+) -> Option<Elements> {
+    if !options.is_read(&token) {
+        return None;
+    }
+    let element = Elements::from(token);
+    let is_raw_text = raw_text_element.is_some_and(is_raw_text_without_references);
+    // the content of a CDATA section is read as is
+    let is_cdata = options.xml && html.starts_with("<![CDATA[");
+    if options.decode_character_references && !is_raw_text && !is_cdata {
+        return Some(decode_element(element));
+    }
+    Some(element)
+}
+
+/// Decode the character references of the attribute values
+pub(crate) fn decode_attributes(attributes: &mut Attributes) {
+    for attribute in attributes.iter_mut() {
+        if let Some(value) = &mut attribute.value {
+            if let Cow::Owned(decoded_value) = decode_attribute_value(value) {
+                *value = decoded_value;
+            }
+        }
+    }
+}
+
+/// Read the token at the beginning of `html`. `begin` is the position of `html` into the whole
+/// HTML document. It returns the token and its length, or `None` if no token is recognized.
+///
+/// The kind of element is chosen from the first characters, then only the element is read : the
+/// HTML code is read once whatever its size.
+pub(crate) fn extract_token(html: &str, begin: usize) -> Option<(Token<'_>, usize)> {
+    match html.as_bytes() {
+        [] => None,
+        [b'<', b'/', ..] => EndElement::extract(html).map(|end_element| {
+            let length = end_element.length;
+            (Token::End(end_element.name, begin, begin + length), length)
+        }),
+        [b'<', b'!', b'-', b'-', ..] => CommentElement::extract(html).map(|comment_element| {
+            let length = comment_element.length;
+            (
+                Token::Comment(comment_element.content, begin, begin + length),
+                length,
+            )
+        }),
+        [b'<', b'!', ..] => DoctypeElement::extract(html).map(|doctype_element| {
+            let length = doctype_element.length;
+            (
+                Token::Doctype(doctype_element.content, begin, begin + length),
+                length,
+            )
+        }),
+        [b'<', ..] => TagRef::extract(html).map(|start_element| {
+            let length = start_element.length;
+            (Token::Start(start_element, begin, begin + length), length)
+        }),
+        _ => TextElement::extract(html).map(|text_element| {
+            let length = text_element.length;
+            (
+                Token::Text(text_element.content, begin, begin + length),
+                length,
+            )
+        }),
+    }
+}
+
+/// Returns true if the character after the first `prefix_length` bytes of `html` is a letter
+fn starts_with_letter(html: &str, prefix_length: usize) -> bool {
+    html.get(prefix_length..)
+        .and_then(|html| html.chars().next())
+        .is_some_and(char::is_alphabetic)
+}
+
+/// Returns true if `html` starts like a comment read until the next `>`, like `<!foo>`, `<?xml?>`
+/// or `</ div>`
+fn is_bogus_comment(html: &str) -> bool {
+    html.starts_with("<!")
+        || html.starts_with("<?")
+        || (html.starts_with("</") && !starts_with_letter(html, 2))
+}
+
+/// Kind of the error of the HTML code that can not be read as an element
+pub(crate) fn parse_error_kind(html: &str) -> ParseErrorKind {
+    let is_doctype = html
+        .get(..9)
+        .is_some_and(|start| start.eq_ignore_ascii_case("<!doctype"));
+
+    let is_tag_start = (html.starts_with("</") && starts_with_letter(html, 2))
+        || (html.starts_with('<') && starts_with_letter(html, 1))
+        || is_doctype;
+
+    if html.starts_with("<!--") {
+        ParseErrorKind::UnterminatedComment
+    } else if is_tag_start && find_tag_end(html).is_none() {
+        ParseErrorKind::UnterminatedTag
+    } else {
+        ParseErrorKind::InvalidMarkup
+    }
+}
+
+/// Returns true if `html`, at the position `begin` into the whole HTML document, starts with an
+/// element like a tag or a comment that can not end, because there is no `>` after
+/// `no_markup_end_from`. Searching its end again would read the rest of the HTML code for each
+/// `<`, like in `<a<a<a`, making the reading quadratic.
+pub(crate) fn is_unterminated_markup(
+    html: &str,
+    begin: usize,
+    no_markup_end_from: Option<usize>,
+    raw_text_element: Option<&str>,
+) -> bool {
+    raw_text_element.is_none()
+        && html.starts_with('<')
+        && no_markup_end_from.is_some_and(|from| begin >= from)
+}
+
+/// Read the HTML code at the beginning of `html` that can not be read as an element, like a
+/// browser does. `begin` is the position of `html` into the whole HTML document. It returns the
+/// token and its length, which is never 0 :
+/// - an unterminated comment is a comment until the end of the HTML code
+/// - `<!foo>`, `<?xml?>` or `</ div>` are comments until the next `>`
+/// - anything else, like `< 3`, `<123` or an unterminated tag, is a text until the next `<`
+pub(crate) fn recover_token(html: &str, begin: usize) -> (Token<'_>, usize) {
+    if let Some(content) = html.strip_prefix("<!--") {
+        return (
+            Token::Comment(content, begin, begin + html.len()),
+            html.len(),
+        );
+    }
+    if is_bogus_comment(html) {
+        // the `?` of `<?xml?>` is part of the comment
+        let start = if html.starts_with("<?") { 1 } else { 2 };
+        let (end, length) = match html.find('>') {
+            Some(end) => (end, end + 1),
+            None => (html.len(), html.len()),
+        };
+        return (
+            Token::Comment(
+                html.get(start..end).unwrap_or_default(),
+                begin,
+                begin + length,
+            ),
+            length,
+        );
+    }
+    let first_char_length = html.chars().next().map_or(0, char::len_utf8);
+    let length = html[first_char_length..]
+        .find('<')
+        .map_or(html.len(), |position| first_char_length + position);
+    (Token::Text(&html[..length], begin, begin + length), length)
+}
+
+/// Elements whose content is text until their end tag. The character references are read in the
+/// content of `textarea` and `title` (RCDATA), but not in the content of `script` and `style`
+/// (RAWTEXT).
+const RAW_TEXT_ELEMENTS: [&str; 4] = ["script", "style", "textarea", "title"];
+
+/// Returns the name of the element whose content is text, like `script`, if `token` starts it
+pub(crate) fn raw_text_element(token: &Token<'_>) -> Option<&'static str> {
+    match token {
+        Token::Start(tag, _, _) if !tag.is_autoclosing => RAW_TEXT_ELEMENTS
+            .iter()
+            .find(|name| tag.name.eq_ignore_ascii_case(name))
+            .copied(),
+        _ => None,
+    }
+}
+
+/// Returns true if the character references are not read in the content of the element
+pub(crate) fn is_raw_text_without_references(element_name: &str) -> bool {
+    element_name == "script" || element_name == "style"
+}
+
+/// Same as `extract_token`, but inside the element `raw_text_element`, like `script`, the HTML
+/// code until the end tag of the element is read as text. If `xml` is true, the CDATA sections
+/// and the processing instructions are read too.
+pub(crate) fn extract_next_token<'a>(
+    html: &'a str,
+    begin: usize,
+    raw_text_element: Option<&str>,
+    xml: bool,
+) -> Option<(Token<'a>, usize)> {
+    raw_text_element
+        .and_then(|element_name| extract_raw_text(html, begin, element_name))
+        .or_else(|| xml.then(|| extract_xml_token(html, begin)).flatten())
+        .or_else(|| extract_token(html, begin))
+}
+
+/// Read the XML markup at the beginning of `html` that is not read in HTML : a CDATA section,
+/// read as a text, or a processing instruction
+fn extract_xml_token(html: &str, begin: usize) -> Option<(Token<'_>, usize)> {
+    if let Some(cdata_element) = CdataElement::extract(html) {
+        let length = cdata_element.length;
+        return Some((
+            Token::Text(cdata_element.content, begin, begin + length),
+            length,
+        ));
+    }
+    ProcessingInstructionElement::extract(html).map(|processing_instruction| {
+        let length = processing_instruction.length;
+        (
+            Token::ProcessingInstruction(processing_instruction.content, begin, begin + length),
+            length,
+        )
+    })
+}
+
+/// Read the content of a raw text element, like `script`, until its end tag. It returns `None`
+/// if the content is empty.
+fn extract_raw_text<'a>(
+    html: &'a str,
+    begin: usize,
+    element_name: &str,
+) -> Option<(Token<'a>, usize)> {
+    let length = find_raw_text_end(html, element_name, 0).unwrap_or(html.len());
+    if length == 0 {
+        None
+    } else {
+        Some((Token::Text(&html[..length], begin, begin + length), length))
+    }
+}
+
+/// Find the position of the end tag of the raw text element, like `</script>`, from `from`
+fn find_raw_text_end(html: &str, element_name: &str, from: usize) -> Option<usize> {
+    let mut position = from;
+    while let Some(offset) = html[position..].find("</") {
+        let end_tag_start = position + offset;
+        let name_end = end_tag_start + 2 + element_name.len();
+        let is_element_name = html
+            .get(end_tag_start + 2..name_end)
+            .is_some_and(|name| name.eq_ignore_ascii_case(element_name));
+        let is_name_ended = html
+            .as_bytes()
+            .get(name_end)
+            .is_some_and(|c| c.is_ascii_whitespace() || *c == b'/' || *c == b'>');
+        if is_element_name && is_name_ended {
+            return Some(end_tag_start);
+        }
+        position = end_tag_start + 2;
+    }
+    None
+}
+
+/// Same as `is_complete_element` for the content of the raw text element, like `script` : the
+/// content is complete when the end tag is found.
+pub(crate) fn is_complete_raw_text(html: &str, element_name: &str, checked_length: usize) -> bool {
+    // the end tag may start before `checked_length`, like `</script` without the `>`
+    let mut from = checked_length
+        .saturating_sub(element_name.len() + 2)
+        .min(html.len());
+    while !html.is_char_boundary(from) {
+        from -= 1;
+    }
+    find_raw_text_end(html, element_name, from).is_some()
+}
+
+/// Returns true if `html` starts with a whole element, i.e. reading more HTML code after it will
+/// not change the element extracted from its beginning.
+///
+/// The end of the element is searched from `checked_length` : the HTML code before it is already
+/// known to not contain the end of the element. The end of a tag is searched with
+/// `tag_end_search`, resumed after the HTML code it already searched. If `xml` is true, the CDATA
+/// sections and the processing instructions are complete at their own end, like `]]>`.
+pub(crate) fn is_complete_element(
+    html: &str,
+    checked_length: usize,
+    tag_end_search: &mut TagEndSearch,
+    xml: bool,
+) -> bool {
+    // the end of an element is at most 3 characters long, like `-->`
+    let mut from = checked_length.saturating_sub(2).min(html.len());
+    while !html.is_char_boundary(from) {
+        from -= 1;
+    }
+    // the end is searched after the start of the element, so `<!-->` does not end a comment
+    let unchecked_html_after = |start: usize| html.get(from.max(start)..).unwrap_or_default();
+    if html.starts_with("<!--") {
+        unchecked_html_after(4).contains("-->")
+    } else if xml && html.starts_with("<![CDATA[") {
+        unchecked_html_after(9).contains("]]>")
+    } else if xml && html.starts_with("<?") && starts_with_letter(html, 2) {
+        unchecked_html_after(2).contains("?>")
+    } else if html.starts_with("</") || is_bogus_comment(html) {
+        unchecked_html_after(0).contains('>')
+    } else if html.starts_with('<') && starts_with_letter(html, 1) {
+        // a `>` inside a quoted attribute value does not end the tag
+        tag_end_search.find(html).is_some()
+    } else if html.starts_with('<') {
+        // `<` is read as a text, like `< 3`
+        unchecked_html_after(1).contains('<')
+    } else {
+        unchecked_html_after(0).contains('<')
+    }
+}
+
 impl Iterator for TagIterator<'_> {
     type Item = Elements;
 
     fn next(&mut self) -> Option<Self::Item> {
-        if self.html.is_empty() {
-            None
-        } else if let Some(start_element) = Tag::extract(self.html) {
-            let begin = self.reading_position;
-            let end = self.reduce_html(start_element.length);
-            Some(Elements::Start(start_element, begin, end))
-        } else if let Some(end_element) = EndElement::extract(self.html) {
-            let begin = self.reading_position;
-            let end = self.reduce_html(end_element.length);
-            Some(Elements::End(end_element.name, begin, end))
-        } else if let Some(comment_element) = CommentElement::extract(self.html) {
-            self.reduce_html(comment_element.length);
-            Some(Elements::Comment(comment_element.content))
-        } else if let Some(text_element) = TextElement::extract(self.html) {
-            self.reduce_html(text_element.length);
-            Some(Elements::Text(text_element.content))
-        } else if let Some(doctype_element) = DoctypeElement::extract(self.html) {
-            self.reduce_html(doctype_element.length);
-            Some(Elements::Doctype(doctype_element.content))
-        } else {
-            None
+        loop {
+            let raw_text_element = self.raw_text_element;
+            let token = self.next_token()?;
+            let element = read_element(
+                token,
+                self.last_element_html,
+                raw_text_element,
+                self.options,
+            );
+            if let Some(mut element) = element {
+                self.last_element_depth = self.open_elements.read(&mut element);
+                return Some(element);
+            }
         }
     }
 }
@@ -71,13 +675,16 @@ mod tag_iterator_tests {
     use Elements::Start;
 
     use super::*;
-    use std::collections::HashMap;
+    use crate::elements::attributes::{Attribute, AttributeQuote, Attributes};
+    use crate::elements::start_element::searched_byte_count;
+    use crate::parse_error::ParseErrorKind;
 
     fn get_simple_div(begin: usize, end: usize) -> Elements {
         Start(
             Tag {
                 name: String::from("div"),
-                attributes: HashMap::new(),
+                original_name: None,
+                attributes: Attributes::new(),
                 length: 5,
                 is_autoclosing: false,
             },
@@ -130,14 +737,36 @@ mod tag_iterator_tests {
 
         assert_eq!(Some(get_simple_div(0, 5)), tag_iterator.next());
 
-        let mut expected_attributes = HashMap::new();
-        expected_attributes.insert("type".to_string(), "password".to_string());
-        expected_attributes.insert("name".to_string(), "password".to_string());
-        expected_attributes.insert("hidden".to_string(), "true".to_string());
+        let mut expected_attributes = Attributes::new();
+        expected_attributes.push(Attribute {
+            name: "type".to_string(),
+            original_name: None,
+            value: Some("password".to_string()),
+            name_span: 7..11,
+            value_span: Some(13..21),
+            quote: AttributeQuote::Single,
+        });
+        expected_attributes.push(Attribute {
+            name: "name".to_string(),
+            original_name: None,
+            value: Some("password".to_string()),
+            name_span: 23..27,
+            value_span: Some(29..37),
+            quote: AttributeQuote::Single,
+        });
+        expected_attributes.push(Attribute {
+            name: "hidden".to_string(),
+            original_name: None,
+            value: None,
+            name_span: 39..45,
+            value_span: None,
+            quote: AttributeQuote::None,
+        });
         assert_eq!(
             Some(Elements::Start(
                 Tag {
                     name: "input".to_string(),
+                    original_name: None,
                     attributes: expected_attributes,
                     length: 48,
                     is_autoclosing: true,
@@ -162,7 +791,7 @@ mod tag_iterator_tests {
         assert_eq!(Some(get_simple_div(0, 5)), tag_iterator.next());
 
         assert_eq!(
-            Some(Elements::Comment(String::from(" hello world "))),
+            Some(Elements::Comment(String::from(" hello world "), 5, 25)),
             tag_iterator.next()
         );
 
@@ -186,7 +815,7 @@ mod tag_iterator_tests {
         world "#
             .to_string();
         assert_eq!(
-            Some(Elements::Comment(expected_comment_content)),
+            Some(Elements::Comment(expected_comment_content, 5, 34)),
             tag_iterator.next()
         );
 
@@ -206,7 +835,7 @@ mod tag_iterator_tests {
         assert_eq!(Some(get_simple_div(0, 5)), tag_iterator.next());
 
         assert_eq!(
-            Some(Elements::Text(String::from("hello world"))),
+            Some(Elements::Text(String::from("hello world"), 5, 16)),
             tag_iterator.next()
         );
 
@@ -229,7 +858,10 @@ mod tag_iterator_tests {
         world"#
             .to_string();
 
-        assert_eq!(Some(Elements::Text(exptected_text)), tag_iterator.next());
+        assert_eq!(
+            Some(Elements::Text(exptected_text, 5, 25)),
+            tag_iterator.next()
+        );
 
         assert_eq!(
             Some(Elements::End(String::from("div"), 25, 31)),
@@ -250,24 +882,456 @@ mod tag_iterator_tests {
         let mut tag_iterator = TagIterator::new(html);
 
         let next = tag_iterator.next().unwrap();
-        assert_eq!(Elements::Text("\n            ".to_string()), next);
+        assert_eq!(Elements::Text("\n            ".to_string(), 0, 13), next);
 
         let next = tag_iterator.next().unwrap();
-        assert_eq!(Elements::Doctype(" html".to_string()), next);
+        assert_eq!(Elements::Doctype(" html".to_string(), 13, 28), next);
 
         let next = tag_iterator.next().unwrap();
-        assert_eq!(Elements::Text("\n            ".to_string()), next);
+        assert_eq!(Elements::Text("\n            ".to_string(), 28, 41), next);
 
         let next = tag_iterator.next().unwrap();
         let tag = Tag {
             name: String::from("html"),
-            attributes: HashMap::new(),
+            original_name: None,
+            attributes: Attributes::new(),
             length: 6,
             is_autoclosing: false,
         };
         assert_eq!(Elements::Start(tag, 41, 47), next);
 
         let next = tag_iterator.next().unwrap();
-        assert_eq!(Elements::Text("\n                ".to_string()), next);
+        assert_eq!(
+            Elements::Text("\n                ".to_string(), 47, 64),
+            next
+        );
+    }
+
+    #[test]
+    fn should_read_the_content_of_script_and_style_as_text() {
+        let html =
+            "<div><script>if (a<b) {} var s = \"</div>\";</SCRIPT><style>p>a{}</style></div>";
+        let elements: Vec<Elements> = TagIterator::new(html)
+            .filter(|element| !matches!(element, Elements::Start(_, _, _)))
+            .collect();
+
+        assert_eq!(
+            vec![
+                Elements::Text("if (a<b) {} var s = \"</div>\";".to_string(), 13, 42),
+                Elements::End("script".to_string(), 42, 51),
+                Elements::Text("p>a{}".to_string(), 58, 63),
+                Elements::End("style".to_string(), 63, 71),
+                Elements::End("div".to_string(), 71, 77),
+            ],
+            elements
+        );
+    }
+
+    #[test]
+    fn should_read_character_references_in_textarea_and_title_only() {
+        let html = "<title>&lt;b&gt;</title><script>a &lt; b</script><textarea/>&amp;";
+        let elements: Vec<Elements> = TagIterator::new(html)
+            .options(ParserOptions::new().decode_character_references(true))
+            .filter(|element| matches!(element, Elements::Text(..)))
+            .collect();
+
+        assert_eq!(
+            vec![
+                Elements::Text("<b>".to_string(), 7, 16),
+                Elements::Text("a &lt; b".to_string(), 32, 40),
+                Elements::Text("&".to_string(), 60, 65),
+            ],
+            elements
+        );
+    }
+
+    #[test]
+    fn should_read_an_empty_or_unclosed_raw_text_element() {
+        let html = "<script></script><script>a</scripts";
+        let elements: Vec<Elements> = TagIterator::new(html)
+            .filter(|element| !matches!(element, Elements::Start(_, _, _)))
+            .collect();
+
+        assert_eq!(
+            vec![
+                Elements::End("script".to_string(), 8, 17),
+                Elements::Text("a</scripts".to_string(), 25, 35),
+            ],
+            elements
+        );
+    }
+
+    #[test]
+    fn should_decode_character_references_only_when_asked() {
+        let html = "<a href='?a=1&amp;b=2'>caf&eacute; &lt;3</a>";
+        let elements: Vec<Elements> = TagIterator::new(html).collect();
+        assert_eq!(
+            Elements::Text("caf&eacute; &lt;3".to_string(), 23, 40),
+            elements[1]
+        );
+
+        let mut tag_iterator =
+            TagIterator::new(html).options(ParserOptions::new().decode_character_references(true));
+        match tag_iterator.next() {
+            Some(Elements::Start(tag, 0, 23)) => {
+                assert_eq!(Some("?a=1&b=2"), tag.attributes.get("href"))
+            }
+            element => panic!("unexpected element {:?}", element),
+        }
+        assert_eq!(
+            Some(Elements::Text("café <3".to_string(), 23, 40)),
+            tag_iterator.next()
+        );
+        assert_eq!("caf&eacute; &lt;3", tag_iterator.last_element_html());
+    }
+
+    #[test]
+    fn should_read_tokens_borrowing_the_html_code() {
+        let html = "<!-- foo --><p class='bar'>hello</p>";
+        let tokens: Vec<Token> = TagIterator::new(html).tokens().collect();
+
+        assert_eq!(4, tokens.len());
+        assert_eq!(Token::Comment(" foo ", 0, 12), tokens[0]);
+        match &tokens[1] {
+            Token::Start(tag, 12, 27) => {
+                assert_eq!("p", tag.name);
+                assert_eq!(Some("bar"), tag.classes());
+            }
+            token => panic!("unexpected token {:?}", token),
+        }
+        assert_eq!(Token::Text("hello", 27, 32), tokens[2]);
+        assert_eq!(Token::End(Cow::Borrowed("p"), 32, 36), tokens[3]);
+    }
+
+    /// Shortest time to read all the elements of `html` with `read`, among a few runs so the noise
+    /// of the other processes is ignored
+    fn reading_time(html: &str, read: impl Fn(&str) -> usize) -> std::time::Duration {
+        (0..3)
+            .map(|_| {
+                let now = std::time::Instant::now();
+                read(html);
+                now.elapsed()
+            })
+            .min()
+            .unwrap()
+    }
+
+    /// Fail if reading `html` with `read` searches the end of the tags in more than 3 times its
+    /// length, like a quadratic reading searching the rest of the HTML code for each tag does
+    fn assert_linear_reading(html: &str, read: impl Fn(&str) -> usize) {
+        let count_before = searched_byte_count();
+        read(html);
+        let count = searched_byte_count() - count_before;
+        assert!(
+            count <= 3 * html.len(),
+            "{} bytes searched for {} bytes",
+            count,
+            html.len()
+        );
+    }
+
+    /// Fail if reading twice as much HTML code takes about 4 times longer, like a quadratic
+    /// reading does, instead of 2 times
+    fn assert_linear_reading_time(
+        build_html: impl Fn(usize) -> String,
+        size: usize,
+        read: impl Fn(&str) -> usize,
+    ) {
+        let time = reading_time(&build_html(size), &read);
+        let double_size_time = reading_time(&build_html(2 * size), &read);
+        assert!(
+            double_size_time < time * 3,
+            "{:?} for size {}, {:?} for size {}",
+            time,
+            size,
+            double_size_time,
+            2 * size
+        );
+    }
+
+    fn count_elements(html: &str) -> usize {
+        TagIterator::new(html).count()
+    }
+
+    fn count_read_elements(html: &str) -> usize {
+        crate::reader_tag_iterator::ReaderTagIterator::with_chunk_size(html.as_bytes(), 1024)
+            .count()
+    }
+
+    #[test]
+    fn should_read_a_large_document_in_linear_time() {
+        // there is no `/>` in the document : searching it on the whole remaining HTML code for
+        // each tag made the reading quadratic.
+        let row = "<tr><td class='cell'>foo</td><td>bar</td></tr>\n";
+        let html = format!("<table>{}</table>", row.repeat(1000));
+        assert_eq!(2 + 1000 * 9, count_elements(&html));
+        assert_eq!(2 + 1000 * 9, count_read_elements(&html));
+
+        assert_linear_reading(&html, count_elements);
+        assert_linear_reading(&html, count_read_elements);
+    }
+
+    #[test]
+    fn should_read_unterminated_tags_in_linear_time() {
+        // there is no `>` in the document : searching it on the whole remaining HTML code for
+        // each `<` made the reading quadratic.
+        let html = "<a".repeat(3);
+        let tokens: Vec<Token> = TagIterator::new(&html).tokens().collect();
+        assert_eq!(
+            vec![
+                Token::Text("<a", 0, 2),
+                Token::Text("<a", 2, 4),
+                Token::Text("<a", 4, 6)
+            ],
+            tokens
+        );
+
+        let html = "<a".repeat(10_000);
+        assert_linear_reading(&html, count_elements);
+        assert_linear_reading(&html, count_read_elements);
+    }
+
+    /// The reading time depends on the other processes : this test is only run on demand, with
+    /// `cargo test -- --ignored`
+    #[test]
+    #[ignore]
+    fn should_read_large_documents_in_linear_time() {
+        let row = "<tr><td class='cell'>foo</td><td>bar</td></tr>\n";
+        let build_html = |rows| format!("<table>{}</table>", row.repeat(rows));
+        assert_linear_reading_time(build_html, 20_000, count_elements);
+        assert_linear_reading_time(build_html, 20_000, count_read_elements);
+
+        let build_html = |count| "<a".repeat(count);
+        assert_linear_reading_time(build_html, 50_000, count_elements);
+        assert_linear_reading_time(build_html, 50_000, count_read_elements);
+    }
+
+    #[test]
+    fn should_stop_at_an_unterminated_comment_and_give_its_position() {
+        let html = "<p>foo</p>\n  <!-- bar";
+        let mut tag_iterator = TagIterator::new(html).options(ParserOptions::new().strict(true));
+
+        assert_eq!(4, tag_iterator.by_ref().count());
+        assert_eq!(
+            Some(ParseError {
+                kind: ParseErrorKind::UnterminatedComment,
+                offset: 13,
+                line: 2,
+                column: 3,
+            }),
+            tag_iterator.take_error()
+        );
+        assert_eq!(None, tag_iterator.next());
+    }
+
+    #[test]
+    fn should_return_the_error_after_the_elements() {
+        let elements: Vec<Result<Elements, ParseError>> = TagIterator::new("<p>foo</p")
+            .options(ParserOptions::new().strict(true))
+            .try_elements()
+            .collect();
+
+        assert_eq!(3, elements.len());
+        assert!(matches!(elements[0], Ok(Elements::Start(_, 0, 3))));
+        assert_eq!(Ok(Elements::Text("foo".to_string(), 3, 6)), elements[1]);
+        assert_eq!(
+            Err(ParseError::new(ParseErrorKind::UnterminatedTag, "<p>foo")),
+            elements[2]
+        );
+    }
+
+    #[test]
+    fn should_give_the_kind_of_the_error() {
+        let htmls = [
+            ("<div class='a", ParseErrorKind::UnterminatedTag),
+            ("</div", ParseErrorKind::UnterminatedTag),
+            ("<!doctype html", ParseErrorKind::UnterminatedTag),
+            ("<!-- a -", ParseErrorKind::UnterminatedComment),
+            ("< 3", ParseErrorKind::InvalidMarkup),
+            ("<123>", ParseErrorKind::InvalidMarkup),
+            ("<!foo>", ParseErrorKind::InvalidMarkup),
+            ("<", ParseErrorKind::InvalidMarkup),
+        ];
+
+        for (html, kind) in htmls.iter() {
+            let mut tag_iterator =
+                TagIterator::new(html).options(ParserOptions::new().strict(true));
+            assert_eq!(
+                Err(ParseError::new(*kind, "")),
+                tag_iterator.try_next_token()
+            );
+        }
+    }
+
+    #[test]
+    fn should_read_non_ascii_texts_attributes_and_tag_names() {
+        let html = "<p title='élève'>€ 61,90</p><café data-é=ü>é</café>été";
+        let elements: Vec<Elements> = TagIterator::new(html).collect();
+
+        assert_eq!(7, elements.len());
+        match &elements[0] {
+            Elements::Start(tag, 0, 19) => {
+                assert_eq!("p", tag.name);
+                assert_eq!(Some("élève"), tag.attributes.get("title"));
+            }
+            element => panic!("unexpected element {:?}", element),
+        }
+        assert_eq!(Elements::Text("€ 61,90".to_string(), 19, 28), elements[1]);
+        assert_eq!(Elements::End("p".to_string(), 28, 32), elements[2]);
+        match &elements[3] {
+            Elements::Start(tag, 32, 50) => {
+                assert_eq!("café", tag.name);
+                assert_eq!(Some("ü"), tag.attributes.get("data-é"));
+            }
+            element => panic!("unexpected element {:?}", element),
+        }
+        assert_eq!(Elements::Text("é".to_string(), 50, 52), elements[4]);
+        assert_eq!(Elements::End("café".to_string(), 52, 60), elements[5]);
+        assert_eq!(Elements::Text("été".to_string(), 60, 65), elements[6]);
+    }
+
+    #[test]
+    fn should_read_the_elements_with_the_options() {
+        let html = "<ul>\n  <li>caf&eacute;</li>\n</ul>";
+        let options = ParserOptions::new()
+            .skip_whitespace_texts(true)
+            .decode_character_references(true);
+        let texts: Vec<Elements> = TagIterator::new(html)
+            .options(options)
+            .filter(|element| matches!(element, Elements::Text(..)))
+            .collect();
+
+        assert_eq!(vec![Elements::Text("café".to_string(), 11, 22)], texts);
+        // the tokens are read as is
+        assert_eq!(7, TagIterator::new(html).options(options).tokens().count());
+    }
+
+    #[test]
+    fn should_fold_the_names_of_the_html_elements() {
+        let html = "<UL CLASS=x><SVG><linearGradient/></SVG></UL>";
+        let elements: Vec<Elements> = TagIterator::new(html).collect();
+
+        match &elements[0] {
+            Start(tag, _, _) => {
+                assert_eq!("ul", tag.name);
+                assert_eq!(Some("UL"), tag.original_name.as_deref());
+                assert_eq!(Some("x"), tag.attributes.get("class"));
+            }
+            element => panic!("unexpected element {:?}", element),
+        }
+        let names: Vec<&str> = elements
+            .iter()
+            .map(|element| match element {
+                Start(tag, _, _) => tag.name.as_str(),
+                Elements::End(name, _, _) => name.as_str(),
+                _ => "",
+            })
+            .collect();
+        assert_eq!(vec!["ul", "svg", "linearGradient", "svg", "ul"], names);
+
+        let options = ParserOptions::new().fold_case(false);
+        let mut elements = TagIterator::new(html).options(options);
+        assert!(matches!(elements.next(), Some(Start(tag, _, _)) if tag.name == "UL"));
+        // the tokens borrow the names as written
+        let mut tokens = TagIterator::new(html).tokens();
+        assert!(matches!(tokens.next(), Some(Token::Start(tag, _, _)) if tag.name == "UL"));
+    }
+
+    #[test]
+    fn should_give_the_depth_of_the_elements() {
+        let depths = |tag_iterator: TagIterator| -> Vec<usize> {
+            tag_iterator.with_depth().map(|(_, depth)| depth).collect()
+        };
+        // <ul> <li> a <li> <br> b </span> </ul>
+        let html = "<ul><li>a<li><br>b</span></ul>";
+        assert_eq!(vec![0, 1, 2, 1, 2, 2, 2, 0], depths(TagIterator::new(html)));
+
+        let options = ParserOptions::new()
+            .void_elements(false)
+            .implied_end_tags(false);
+        assert_eq!(
+            vec![0, 1, 2, 2, 3, 4, 4, 0],
+            depths(TagIterator::new(html).options(options))
+        );
+        // <feed> <link/> <entry> </entry> </feed>
+        let xml = "<feed><link/><entry></entry></feed>";
+        assert_eq!(
+            vec![0, 1, 1, 1, 0],
+            depths(TagIterator::new(xml).options(ParserOptions::new().xml(true)))
+        );
+    }
+
+    #[test]
+    fn should_read_cdata_sections_and_processing_instructions_in_xml() {
+        let xml = "<?xml version='1.0'?><a><![CDATA[x &amp; <y>]]><script>&amp;<b/></script></a>";
+        let options = ParserOptions::new()
+            .xml(true)
+            .decode_character_references(true);
+        let elements: Vec<Elements> = TagIterator::new(xml)
+            .options(options)
+            .filter(|element| !matches!(element, Elements::Start(..) | Elements::End(..)))
+            .collect();
+
+        assert_eq!(
+            vec![
+                Elements::ProcessingInstruction("xml version='1.0'".to_string(), 0, 21),
+                Elements::Text("x &amp; <y>".to_string(), 24, 47),
+                Elements::Text("&".to_string(), 55, 60),
+            ],
+            elements
+        );
+
+        // in HTML, they are comments
+        let elements: Vec<Elements> = TagIterator::new(xml).take(3).collect();
+        assert_eq!(
+            Elements::Comment("?xml version='1.0'?".to_string(), 0, 21),
+            elements[0]
+        );
+        assert_eq!(
+            Elements::Comment("[CDATA[x &amp; <y".to_string(), 24, 44),
+            elements[2]
+        );
+    }
+
+    #[test]
+    fn should_read_unrecognized_markup_like_a_browser() {
+        let html = "<p>1 < 3 <123 <!foo> <?xml version='1.0'?></ div><</p><div class='a";
+        let elements: Vec<Elements> = TagIterator::new(html).skip(1).collect();
+
+        assert_eq!(
+            vec![
+                Elements::Text("1 ".to_string(), 3, 5),
+                Elements::Text("< 3 ".to_string(), 5, 9),
+                Elements::Text("<123 ".to_string(), 9, 14),
+                Elements::Comment("foo".to_string(), 14, 20),
+                Elements::Text(" ".to_string(), 20, 21),
+                Elements::Comment("?xml version='1.0'?".to_string(), 21, 42),
+                Elements::Comment(" div".to_string(), 42, 49),
+                Elements::Text("<".to_string(), 49, 50),
+                Elements::End("p".to_string(), 50, 54),
+                Elements::Text("<div class='a".to_string(), 54, 67),
+            ],
+            elements
+        );
+    }
+
+    #[test]
+    fn should_never_panic() {
+        let html = "<!DOCTYPE html><html><head><title>a &amp b</title><script>if (a<b) {}</scr\
+            </script></head><body><!-->--><!----><p class=\"x\" a=b c='d' e>é€<br/>\
+            <img src='a/>b'></P></span>< 3 <123 <!foo> </ div>&#x110000;&#0;&amp<div\n a\n=\n'b'>";
+
+        for (end, _) in html.char_indices() {
+            let html = &html[..end];
+            let elements_count = TagIterator::new(html)
+                .options(ParserOptions::new().decode_character_references(true))
+                .try_elements()
+                .count();
+            let reader_elements_count =
+                crate::reader_tag_iterator::ReaderTagIterator::with_chunk_size(html.as_bytes(), 1)
+                    .count();
+            assert!(reader_elements_count <= elements_count);
+            let _ = crate::by_name::try_find_first(html, &["p", "title"]);
+        }
     }
 }
@@ -1,10 +1,84 @@
 use std::borrow::Borrow;
 
-use crate::elements::start_element::Tag;
+use crate::elements::attributes::Attribute;
+use crate::elements::start_element::{split_qualified_name, Tag, TagRef};
+use crate::parser_options::ParserOptions;
+use crate::tag_iterator::decode_attributes;
 
 pub struct TagPathItem {
     pub tag: Box<Tag>,
     pub nth_child: usize,
+    pub namespace: Namespace,
+}
+
+/// Namespace of an element : the elements inside an `<svg>` or a `<math>` are foreign content
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Namespace {
+    #[default]
+    Html,
+    Svg,
+    MathMl,
+}
+
+impl Namespace {
+    /// Prefix of the namespace in the CSS selectors, like `svg` in `svg|rect`
+    pub fn prefix(&self) -> &'static str {
+        match self {
+            Namespace::Html => "html",
+            Namespace::Svg => "svg",
+            Namespace::MathMl => "math",
+        }
+    }
+
+    /// Returns true if the case folding lowercases the names of the element named `tag_name` in
+    /// this namespace, like an HTML parser does : the names of the HTML elements, and of the
+    /// `<svg>` or `<math>` element starting the foreign content. The other names of the foreign
+    /// content keep their case, like `<linearGradient>`.
+    pub fn folds_case(&self, tag_name: &str) -> bool {
+        let local_name = split_qualified_name(tag_name).1;
+        *self == Namespace::Html
+            || local_name.eq_ignore_ascii_case("svg")
+            || local_name.eq_ignore_ascii_case("math")
+    }
+
+    /// Namespace of the element named `tag_name` inside `parent`, like the HTML parser finds it :
+    /// `<svg>` and `<math>` start the foreign content, and the text elements of the foreign
+    /// content, like `<foreignObject>` or `<mtext>`, contain HTML elements again.
+    pub fn of_element(parent: Option<&TagPathItem>, tag_name: &str) -> Namespace {
+        let parent = parent.map(|parent| (parent.namespace, parent.tag.name.as_str()));
+        Namespace::of_child(parent, tag_name)
+    }
+
+    /// Namespace of the element named `tag_name` inside the element of namespace and name
+    /// `parent`, like `of_element`
+    pub(crate) fn of_child(parent: Option<(Namespace, &str)>, tag_name: &str) -> Namespace {
+        let local_name = split_qualified_name(tag_name).1;
+        if local_name.eq_ignore_ascii_case("svg") {
+            return Namespace::Svg;
+        }
+        if local_name.eq_ignore_ascii_case("math") {
+            return Namespace::MathMl;
+        }
+        let (parent_namespace, parent_name) = match parent {
+            Some(parent) => parent,
+            None => return Namespace::Html,
+        };
+        let parent_name = split_qualified_name(parent_name).1;
+        let is_html_integration_point = match parent_namespace {
+            Namespace::Html => false,
+            Namespace::Svg => ["foreignObject", "desc", "title"]
+                .iter()
+                .any(|name| parent_name.eq_ignore_ascii_case(name)),
+            Namespace::MathMl => ["mi", "mo", "mn", "ms", "mtext"]
+                .iter()
+                .any(|name| parent_name.eq_ignore_ascii_case(name)),
+        };
+        if is_html_integration_point {
+            Namespace::Html
+        } else {
+            parent_namespace
+        }
+    }
 }
 
 /// Format a tag path item with 2 informations : tag's id or '' and nth-child index (always)
@@ -12,7 +86,7 @@ impl std::fmt::Debug for TagPathItem {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let nth_child = format!(":nth-child({})", self.nth_child);
         let id = if self.tag.id().is_none() {
-            format!("")
+            String::new()
         } else {
             format!("#{}", self.tag.id().unwrap())
         };
@@ -23,29 +97,77 @@ impl std::fmt::Debug for TagPathItem {
 
 #[derive(Debug)]
 pub struct TagPath {
-    path: Vec<TagPathItem>,
+    /// open elements, with their tag
+    path: OpenElements<TagPathItem>,
     last_popped_tag: Option<(TagPathItem, usize)>, // FIXME : should we have a vector of last popped tag ?
+    /// options like the case folding of the names or the XML mode
+    options: ParserOptions,
+    /// items removed from the path, whose memory is reused by the next tags read
+    unused_items: Vec<TagPathItem>,
+    /// attributes of the tags removed from the path, whose memory is reused by the next tags
+    unused_attributes: Vec<Attribute>,
 }
 
 impl TagPath {
     pub fn new() -> Self {
         TagPath {
-            path: Vec::new(),
+            path: OpenElements::new(),
             last_popped_tag: None,
+            options: ParserOptions::new(),
+            unused_items: Vec::new(),
+            unused_attributes: Vec::new(),
         }
     }
-    pub fn add(&mut self, tag: Tag) {
+    /// Read the tags with `options`. With the case folding, the names of the HTML elements and of
+    /// their attributes are lowercased, like `<TD VALIGN=top>`, so they are matched like HTML
+    /// parsers do. The names keep their case in XML and in foreign content, like
+    /// `<linearGradient>` inside an `<svg>`. In XML, only the tags written like `<div/>` have no
+    /// end tag, and no end tag is implied, like the end tag of a `<p>`.
+    pub fn options(mut self, options: ParserOptions) -> Self {
+        self.options = options;
+        self.path = self.path.options(options);
+        self
+    }
+    /// Add the start tag `tag` to the path. The memory of the tags removed from the path is
+    /// reused, so the tags are added without allocating once their strings are long enough. The
+    /// character references of the attribute values are decoded if the options ask for it.
+    pub fn add(&mut self, tag: TagRef<'_>) {
+        let tag_name = tag.name;
+        let mut tag = match self.unused_items.pop() {
+            Some(mut unused_item) => {
+                unused_item.tag.refill(tag, &mut self.unused_attributes);
+                unused_item.tag
+            }
+            None => Box::new(Tag::from(tag)),
+        };
+        if self.options.decode_character_references {
+            decode_attributes(&mut tag.attributes);
+        }
+        // the start tag may close the last tags, like a `<li>` closes an open `<li>`
+        for _ in 0..self.count_implied_end_tags(tag_name) {
+            self.reduce();
+        }
+
         #[cfg(test)]
         println!(
             "\nADD - {:?}\n\t- new tag {:?}\n\t- last popped {:?}",
-            self.path, tag, self.last_popped_tag
+            self.path.items(),
+            tag,
+            self.last_popped_tag
         );
 
+        // the names are folded first, so `<TR>` is the sibling of `<tr>`, and `<SVG>` starts the
+        // foreign content like `<svg>`
+        let namespace = self.path.namespace_of(tag_name);
+        if self.path.folds_case(namespace, tag_name) {
+            tag.fold_case();
+        }
+
         let next_nth_child = match self.last_popped_tag.borrow() {
             Some((last_tag_path_item, depth)) => {
                 // if the depth of the last popped tag is lower than the actual path length,
                 // then the last popped tag was not meaningfull
-                if *depth < self.path.len() {
+                if *depth < self.path.depth() {
                     1
                 } else {
                     let last_tag_name = &last_tag_path_item.tag.name;
@@ -60,39 +182,376 @@ impl TagPath {
             None => 1,
         };
 
-        self.path.push(TagPathItem {
-            tag: Box::new(tag),
+        let item = TagPathItem {
+            tag,
             nth_child: next_nth_child,
-        });
+            namespace,
+        };
+        self.path.push(tag_name, namespace, item);
 
         #[cfg(test)]
-        println!("\t==> path {:?}", self.path);
+        println!("\t==> path {:?}", self.path.items());
     }
     pub fn reduce(&mut self) {
         #[cfg(test)]
         println!(
             "\nREDUCEing - {:?}\n\t- popped tag {:?}",
-            self.path, self.last_popped_tag
+            self.path.items(),
+            self.last_popped_tag
         );
-        let len = self.path.len();
-        self.last_popped_tag = self.path.pop().map(|t| (t, len));
+        let len = self.path.depth();
+        let popped_tag = self.path.pop().map(|t| (t, len));
+        if let Some((item, _)) = std::mem::replace(&mut self.last_popped_tag, popped_tag) {
+            self.unused_items.push(item);
+        }
         #[cfg(test)]
         println!(
             "\t==>- {:?}\n\t- popped tag {:?}",
-            self.path, self.last_popped_tag
+            self.path.items(),
+            self.last_popped_tag
         );
     }
 
+    /// Close the open element named `end_tag_name` and the open elements inside it. An end tag
+    /// that matches no open element is ignored.
+    pub fn close(&mut self, end_tag_name: &str) -> EndTagRecovery {
+        let recovery = self.path.match_end_tag(end_tag_name);
+        for _ in 0..recovery.closed_count() {
+            self.reduce();
+        }
+        recovery
+    }
+
+    /// Count the tags closed by the start tag `tag_name` because their end tag is optional
+    pub fn count_implied_end_tags(&self, tag_name: &str) -> usize {
+        self.path.count_implied_end_tags(tag_name)
+    }
+
+    /// Returns true if the end tag `tag_name` is ignored, like the end tag of a void element
+    pub fn is_ignored_end_tag(&self, tag_name: &str) -> bool {
+        self.path.is_ignored_end_tag(tag_name)
+    }
+
+    /// Remove the last tag added if it has no end tag, like `<br>` or `<div/>`. It is called
+    /// once the tag path with this tag is read.
+    pub fn close_self_closing_tag(&mut self) {
+        if self.last().is_some_and(|item| {
+            self.path
+                .is_self_closing(&item.tag.name, item.tag.is_autoclosing)
+        }) {
+            self.reduce();
+        }
+    }
+
+    /// Last tag added to the path, if it is still open
+    pub fn last(&self) -> Option<&TagPathItem> {
+        self.path.items().last()
+    }
+
     pub fn get_matching_path(&self) -> Vec<&TagPathItem> {
-        self.path
-            .iter()
-            .collect()
+        self.path.items().iter().collect()
+    }
+}
+
+/// Elements open around the next element read, opened and closed like an HTML parser does with
+/// the options : the void elements have no end tag, a start tag may imply the end tag of open
+/// elements, like a `<li>` closes an open `<li>`, and an end tag closes the open elements inside
+/// its element. Their namespace tells which names the case folding lowercases. Each open element
+/// keeps an item, like its tag in the tag path.
+#[derive(Debug)]
+pub(crate) struct OpenElements<T = ()> {
+    /// names of the open elements from the root, separated by a space, like `html body div`
+    names: String,
+    /// namespace of each open element, from the root
+    namespaces: Vec<Namespace>,
+    /// item of each open element, from the root
+    items: Vec<T>,
+    options: ParserOptions,
+}
+
+impl<T> Default for OpenElements<T> {
+    fn default() -> Self {
+        OpenElements::new()
+    }
+}
+
+impl<T> OpenElements<T> {
+    pub(crate) fn new() -> Self {
+        OpenElements {
+            names: String::new(),
+            namespaces: Vec::new(),
+            items: Vec::new(),
+            options: ParserOptions::new(),
+        }
+    }
+
+    /// Open and close the elements with `options`, like the void elements, the implied end tags,
+    /// the case folding or the XML mode
+    pub(crate) fn options(mut self, options: ParserOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Names of the open elements from the root, separated by a space, like `html body div`
+    pub(crate) fn names_path(&self) -> &str {
+        &self.names
+    }
+
+    /// Names of the open elements, from the root
+    pub(crate) fn names(&self) -> impl DoubleEndedIterator<Item = &str> + Clone + '_ {
+        // a tag name never contains a space
+        self.names.split(' ').filter(|name| !name.is_empty())
+    }
+
+    /// Count of the open elements
+    pub(crate) fn depth(&self) -> usize {
+        self.items.len()
+    }
+
+    /// Items of the open elements, from the root
+    pub(crate) fn items(&self) -> &[T] {
+        &self.items
+    }
+
+    /// Returns true if the element named `tag_name` has no end tag : its start tag is written
+    /// like `<br/>`, or it is a void element like `<br>` when the options read the void elements
+    pub(crate) fn is_self_closing(&self, tag_name: &str, is_autoclosing: bool) -> bool {
+        self.options.is_self_closing(tag_name, is_autoclosing)
+    }
+
+    /// Returns true if the end tag named `tag_name` is ignored : in HTML, the end tag of a void
+    /// element, like `</br>`, is ignored
+    pub(crate) fn is_ignored_end_tag(&self, tag_name: &str) -> bool {
+        self.options.is_void_element(tag_name)
+    }
+
+    /// Count the open elements closed by the start tag named `tag_name` because their end tag is
+    /// optional. No end tag is implied in XML.
+    pub(crate) fn count_implied_end_tags(&self, tag_name: &str) -> usize {
+        if !self.options.has_implied_end_tags() {
+            return 0;
+        }
+        count_implied_end_tags(self.names(), tag_name)
+    }
+
+    /// Namespace of the element named `tag_name` opened in the current element
+    pub(crate) fn namespace_of(&self, tag_name: &str) -> Namespace {
+        let parent = self
+            .namespaces
+            .last()
+            .copied()
+            .zip(self.names().next_back());
+        Namespace::of_child(parent, tag_name)
+    }
+
+    /// Returns true if the case folding lowercases the names of the element named `tag_name` in
+    /// `namespace`
+    pub(crate) fn folds_case(&self, namespace: Namespace, tag_name: &str) -> bool {
+        self.options.has_fold_case() && namespace.folds_case(tag_name)
+    }
+
+    /// Returns true if the case folding lowercases the end tag named `tag_name`, read in the
+    /// current element
+    pub(crate) fn folds_end_tag_case(&self, tag_name: &str) -> bool {
+        let namespace = self.namespaces.last().copied().unwrap_or_default();
+        self.folds_case(namespace, tag_name)
+    }
+
+    /// Match the end tag named `tag_name` with the innermost open element of the same name. The
+    /// names are compared case-insensitively, except in XML.
+    pub(crate) fn match_end_tag(&self, tag_name: &str) -> EndTagRecovery {
+        match_end_tag(self.names(), tag_name, self.options.xml)
+    }
+
+    /// Open the element named `tag_name` of `namespace` with its item. Its name is lowercased if
+    /// the case folding lowercases it.
+    pub(crate) fn push(&mut self, tag_name: &str, namespace: Namespace, item: T) {
+        if !self.names.is_empty() {
+            self.names.push(' ');
+        }
+        let name_start = self.names.len();
+        self.names.push_str(tag_name);
+        if self.folds_case(namespace, tag_name) {
+            self.names[name_start..].make_ascii_lowercase();
+        }
+        self.namespaces.push(namespace);
+        self.items.push(item);
+    }
+
+    /// Close the current element. It returns its item.
+    pub(crate) fn pop(&mut self) -> Option<T> {
+        let length = self.names.rfind(' ').unwrap_or(0);
+        self.names.truncate(length);
+        self.namespaces.pop();
+        self.items.pop()
+    }
+
+    /// Close the open elements until `depth` elements are open
+    pub(crate) fn truncate(&mut self, depth: usize) {
+        while self.depth() > depth {
+            self.pop();
+        }
+    }
+
+    /// Close all the open elements
+    pub(crate) fn clear(&mut self) {
+        self.names.clear();
+        self.namespaces.clear();
+        self.items.clear();
+    }
+}
+
+/// Elements whose end tag is optional, closed by a start tag. The start tag closes the open
+/// elements up to the outermost one in `closed_tags`, searching from the current element and
+/// stopping at an element in `scope_tags`.
+struct ImpliedEndTagRule {
+    start_tags: &'static [&'static str],
+    closed_tags: &'static [&'static str],
+    scope_tags: &'static [&'static str],
+}
+
+#[rustfmt::skip]
+const IMPLIED_END_TAG_RULES: [ImpliedEndTagRule; 9] = [
+    ImpliedEndTagRule {
+        start_tags: &[
+            "address", "article", "aside", "blockquote", "center", "dd", "details", "dialog", "dir",
+            "div", "dl", "dt", "fieldset", "figcaption", "figure", "footer", "form", "h1", "h2",
+            "h3", "h4", "h5", "h6", "header", "hgroup", "hr", "li", "listing", "main", "menu", "nav",
+            "ol", "p", "pre", "section", "summary", "table", "ul",
+        ],
+        closed_tags: &["p"],
+        scope_tags: &[
+            "applet", "button", "caption", "html", "marquee", "object", "table", "td", "template",
+            "th",
+        ],
+    },
+    ImpliedEndTagRule {
+        start_tags: &["li"],
+        closed_tags: &["li"],
+        scope_tags: &["ol", "ul", "menu", "table"],
+    },
+    ImpliedEndTagRule {
+        start_tags: &["dd", "dt"],
+        closed_tags: &["dd", "dt"],
+        scope_tags: &["dl", "table"],
+    },
+    ImpliedEndTagRule {
+        start_tags: &["tbody", "thead", "tfoot"],
+        closed_tags: &["tbody", "thead", "tfoot"],
+        scope_tags: &["table"],
+    },
+    ImpliedEndTagRule {
+        start_tags: &["tr"],
+        closed_tags: &["tr"],
+        scope_tags: &["table", "tbody", "thead", "tfoot"],
+    },
+    ImpliedEndTagRule {
+        start_tags: &["td", "th"],
+        closed_tags: &["td", "th"],
+        scope_tags: &["table", "tr"],
+    },
+    ImpliedEndTagRule {
+        start_tags: &["option"],
+        closed_tags: &["option"],
+        scope_tags: &["select", "datalist", "optgroup"],
+    },
+    ImpliedEndTagRule {
+        start_tags: &["optgroup"],
+        closed_tags: &["option", "optgroup"],
+        scope_tags: &["select"],
+    },
+    ImpliedEndTagRule {
+        start_tags: &["rp", "rt"],
+        closed_tags: &["rp", "rt"],
+        scope_tags: &["ruby"],
+    },
+];
+
+/// Count the open elements closed by the start tag `tag_name` because their end tag is optional,
+/// like a `<li>` closes an open `<li>`, or a `<tr>` closes an open `<td>` and its `<tr>`.
+/// `open_tag_names` are the names of the open elements, from the root.
+pub fn count_implied_end_tags<'a, I>(open_tag_names: I, tag_name: &str) -> usize
+where
+    I: DoubleEndedIterator<Item = &'a str> + Clone,
+{
+    let contains = |names: &[&str], name: &str| names.iter().any(|n| n.eq_ignore_ascii_case(name));
+
+    IMPLIED_END_TAG_RULES
+        .iter()
+        .filter(|rule| contains(rule.start_tags, tag_name))
+        .filter_map(|rule| {
+            open_tag_names
+                .clone()
+                .rev()
+                .take_while(|name| !contains(rule.scope_tags, name))
+                .enumerate()
+                .filter(|(_, name)| contains(rule.closed_tags, name))
+                .last()
+                .map(|(position, _)| position + 1)
+        })
+        .max()
+        .unwrap_or(0)
+}
+
+/// How an end tag is matched with the open elements
+#[derive(Debug, Clone, PartialEq)]
+pub enum EndTagRecovery {
+    /// The end tag closes the current element
+    Matched,
+    /// The end tag closes an outer element, so the open elements inside it are closed too. They
+    /// are listed from the current element.
+    ClosedOpenElements(Vec<String>),
+    /// No open element has the name of the end tag, so the end tag is ignored
+    Ignored,
+}
+
+impl EndTagRecovery {
+    /// Count of the open elements closed by the end tag
+    pub fn closed_count(&self) -> usize {
+        match self {
+            EndTagRecovery::Matched => 1,
+            EndTagRecovery::ClosedOpenElements(names) => names.len() + 1,
+            EndTagRecovery::Ignored => 0,
+        }
+    }
+}
+
+/// Match the end tag `end_tag_name` with the innermost open element of the same name, comparing
+/// names case-insensitively unless `is_case_sensitive` is true. `open_tag_names` are the names of
+/// the open elements, from the root.
+pub fn match_end_tag<'a, I>(
+    open_tag_names: I,
+    end_tag_name: &str,
+    is_case_sensitive: bool,
+) -> EndTagRecovery
+where
+    I: DoubleEndedIterator<Item = &'a str> + Clone,
+{
+    let is_same_name = |name: &str| {
+        if is_case_sensitive {
+            name == end_tag_name
+        } else {
+            name.eq_ignore_ascii_case(end_tag_name)
+        }
+    };
+    let mut open_tag_names = open_tag_names.rev();
+    match open_tag_names.clone().position(is_same_name) {
+        Some(0) => EndTagRecovery::Matched,
+        Some(position) => EndTagRecovery::ClosedOpenElements(
+            open_tag_names
+                .by_ref()
+                .take(position)
+                .map(str::to_string)
+                .collect(),
+        ),
+        None => EndTagRecovery::Ignored,
     }
 }
 
 /// match a `tag_path` as read by the HTML stream reader with a CSS selector `css_selector`.
 /// - tag_path is a vector where each element match an HTML tag. Each element indexed N has its parent at index N-1
 /// - css_selector is a vector of predicate. Each element of the vector will match one tag at a given index of the tag_path.
+///
 /// This is a recursive algorithm where it tries to match the last element of the tag_path and go backwards to its parent.
 pub fn match_tag_path<F>(tag_path: Vec<&TagPathItem>, css_selector: &[F]) -> bool
 where
@@ -117,14 +576,17 @@ fn match_tag_path_index<F>(
 where
     F: Fn(&TagPathItem) -> bool,
 {
-    // FIXME unwrap
-    let tag = tag_path.get(tag_index).unwrap();
-    let selector = css_selector.get(selector_index).unwrap();
+    let (tag, selector) = match (tag_path.get(tag_index), css_selector.get(selector_index)) {
+        (Some(tag), Some(selector)) => (tag, selector),
+        _ => return false,
+    };
     if selector(tag) {
-        if selector_index == 0 {
-            true
-        } else {
-            match_tag_path_index(tag_path, tag_index - 1, css_selector, selector_index - 1)
+        match (tag_index.checked_sub(1), selector_index.checked_sub(1)) {
+            (_, None) => true,
+            (Some(tag_index), Some(selector_index)) => {
+                match_tag_path_index(tag_path, tag_index, css_selector, selector_index)
+            }
+            (None, Some(_)) => false,
         }
     } else {
         false
@@ -137,37 +599,48 @@ mod test_tag_path {
 
     use super::*;
     use crate::css_selector;
-    use std::collections::HashMap;
+    use crate::elements::attributes::{Attribute, AttributeQuote, Attributes};
 
     fn build_tag_with_attribute(
         name: &str,
         attribute_key: &str,
         attribute_value: &str,
     ) -> TagPathItem {
-        let mut map = HashMap::new();
-        map.insert(attribute_key.to_string(), attribute_value.to_string());
+        let mut attributes = Attributes::new();
+        attributes.push(Attribute {
+            name: attribute_key.to_string(),
+            original_name: None,
+            value: Some(attribute_value.to_string()),
+            name_span: 0..0,
+            value_span: Some(0..0),
+            quote: AttributeQuote::Double,
+        });
         let tag = Tag {
             name: name.to_string(),
-            attributes: map,
+            original_name: None,
+            attributes,
             length: 0,
             is_autoclosing: false,
         };
         TagPathItem {
             tag: Box::new(tag),
             nth_child: 1,
+            namespace: Namespace::Html,
         }
     }
 
     fn build_tag(name: &str) -> TagPathItem {
         let tag = Tag {
             name: name.to_string(),
-            attributes: HashMap::new(),
+            original_name: None,
+            attributes: Attributes::new(),
             length: 0,
             is_autoclosing: false,
         };
         TagPathItem {
             tag: Box::new(tag),
             nth_child: 1,
+            namespace: Namespace::Html,
         }
     }
 
@@ -231,10 +704,10 @@ mod test_tag_path {
 mod test_tag_path_nth {
 
     use super::*;
-    use crate::elements::{start_element::Tag, Element};
+    use crate::elements::{start_element::TagRef, Element};
 
-    fn create_tag(html: &str) -> Tag {
-        Tag::extract(html).expect("invalid HTML tag for testing")
+    fn create_tag(html: &str) -> TagRef<'_> {
+        TagRef::extract(html).expect("invalid HTML tag for testing")
     }
 
     #[test]
@@ -284,6 +757,7 @@ mod test_tag_path_nth {
     fn assert_nth_child_at(tag_path: &TagPath, index: usize, expected_nth_child: usize) {
         let tag_path_item = tag_path
             .path
+            .items()
             .get(index)
             .expect("invalid position of the tag for test");
         assert_eq!(expected_nth_child, tag_path_item.nth_child);
@@ -293,7 +767,7 @@ mod test_tag_path_nth {
 #[cfg(test)]
 mod test_tag_path_nth_child {
     use super::*;
-    use crate::tag_iterator::{Elements, TagIterator};
+    use crate::tag_iterator::{TagIterator, Token};
 
     #[test]
     fn should_match_all_tag_nth_child() {
@@ -327,10 +801,10 @@ mod test_tag_path_nth_child {
 
         let mut start_element_index = 0;
 
-        let tag_iterator = TagIterator::new(&html);
-        tag_iterator.enumerate().for_each(|(_index, element)| {
-            match element {
-                Elements::Start(tag, _begin, _end) => {
+        let tokens = TagIterator::new(&html).tokens();
+        tokens.for_each(|token| {
+            match token {
+                Token::Start(tag, _begin, _end) => {
                     tag_path.add(tag);
                     assert_tag_path(
                         &tag_path,
@@ -338,23 +812,221 @@ mod test_tag_path_nth_child {
                             .get(start_element_index)
                             .expect("expected value required for test"),
                     );
-                    start_element_index = start_element_index + 1;
+                    start_element_index += 1;
                 }
-                Elements::End(_, _, _) => {
+                Token::End(_, _, _) => {
                     tag_path.reduce();
                 }
-                //Elements::Comment(tag) => {},
-                //Elements::Text(tag) => {},
+                //Token::Comment(tag) => {},
+                //Token::Text(tag) => {},
                 _ => {}
             }
         });
     }
 
+    #[test]
+    fn should_count_the_tags_closed_by_a_start_tag() {
+        let names = ["ul", "li", "p", "span"];
+        assert_eq!(3, count_implied_end_tags(names.iter().copied(), "li"));
+        assert_eq!(2, count_implied_end_tags(names.iter().copied(), "div"));
+        assert_eq!(0, count_implied_end_tags(names.iter().copied(), "span"));
+
+        let names = ["li", "ul", "li"];
+        assert_eq!(1, count_implied_end_tags(names.iter().copied(), "li"));
+
+        let names = ["table", "tbody", "tr", "td", "table", "tr", "td"];
+        assert_eq!(2, count_implied_end_tags(names.iter().copied(), "TR"));
+        assert_eq!(1, count_implied_end_tags(names.iter().copied(), "th"));
+        assert_eq!(0, count_implied_end_tags(names.iter().copied(), "option"));
+    }
+
+    #[test]
+    fn should_close_tags_whose_end_tag_is_omitted() {
+        let html =
+            "<table><tr><td>a<td>b<tr><td>c<p>d<td>e</td></tr></table><ul><li>f<li>g</li></ul>";
+        let mut tag_path = TagPath::new();
+
+        let expected_paths = vec![
+            "table:nth-child(1)".to_string(),
+            "table:nth-child(1) tr:nth-child(1)".to_string(),
+            "table:nth-child(1) tr:nth-child(1) td:nth-child(1)".to_string(),
+            "table:nth-child(1) tr:nth-child(1) td:nth-child(2)".to_string(),
+            "table:nth-child(1) tr:nth-child(2)".to_string(),
+            "table:nth-child(1) tr:nth-child(2) td:nth-child(1)".to_string(),
+            "table:nth-child(1) tr:nth-child(2) td:nth-child(1) p:nth-child(1)".to_string(),
+            "table:nth-child(1) tr:nth-child(2) td:nth-child(2)".to_string(),
+            "ul:nth-child(1)".to_string(),
+            "ul:nth-child(1) li:nth-child(1)".to_string(),
+            "ul:nth-child(1) li:nth-child(2)".to_string(),
+        ];
+
+        let mut start_element_index = 0;
+        for token in TagIterator::new(html).tokens() {
+            match token {
+                Token::Start(tag, _begin, _end) => {
+                    tag_path.add(tag);
+                    assert_tag_path(&tag_path, &expected_paths[start_element_index]);
+                    start_element_index += 1;
+                }
+                Token::End(_, _, _) => {
+                    tag_path.reduce();
+                }
+                _ => {}
+            }
+        }
+        assert_eq!(expected_paths.len(), start_element_index);
+    }
+
+    #[test]
+    fn should_match_end_tags_with_the_open_elements() {
+        let open_tag_names = ["html", "body", "div", "p", "span"];
+        let recovery = |name| match_end_tag(open_tag_names.iter().copied(), name, false);
+
+        assert_eq!(EndTagRecovery::Matched, recovery("span"));
+        assert_eq!(EndTagRecovery::Matched, recovery("SPAN"));
+        assert_eq!(
+            EndTagRecovery::ClosedOpenElements(vec!["span".to_string(), "p".to_string()]),
+            recovery("div")
+        );
+        assert_eq!(EndTagRecovery::Ignored, recovery("table"));
+        assert_eq!(3, recovery("div").closed_count());
+        assert_eq!(0, recovery("table").closed_count());
+    }
+
+    #[test]
+    fn should_read_xml_tags_without_html_rules() {
+        let options = ParserOptions::new().xml(true);
+        let mut tag_path = TagPath::new().options(options);
+        let tag_iterator = TagIterator::new("<feed><link><p><p><entry/>").options(options);
+        for token in tag_iterator.tokens() {
+            if let Token::Start(tag, _begin, _end) = token {
+                tag_path.add(tag);
+                tag_path.close_self_closing_tag();
+            }
+        }
+        assert_tag_path(
+            &tag_path,
+            &"feed:nth-child(1) link:nth-child(1) p:nth-child(1) p:nth-child(1)".to_string(),
+        );
+
+        assert_eq!(EndTagRecovery::Ignored, tag_path.close("P"));
+        assert_eq!(EndTagRecovery::Matched, tag_path.close("p"));
+        assert_eq!(
+            EndTagRecovery::ClosedOpenElements(vec!["p".to_string()]),
+            tag_path.close("link")
+        );
+        assert_tag_path(&tag_path, &"feed:nth-child(1)".to_string());
+    }
+
+    #[test]
+    fn should_give_the_namespace_of_foreign_content() {
+        let mut tag_path = TagPath::new();
+        let mut namespaces = Vec::new();
+        let html = "<div><svg><linearGradient></linearGradient><foreignObject><p>\
+            <math><mi><b>";
+        for token in TagIterator::new(html).tokens() {
+            match token {
+                Token::Start(tag, _begin, _end) => {
+                    tag_path.add(tag);
+                    let item = tag_path.get_matching_path().pop().unwrap();
+                    namespaces.push((item.tag.name.clone(), item.namespace));
+                }
+                Token::End(name, _begin, _end) => {
+                    tag_path.close(&name);
+                }
+                _ => {}
+            }
+        }
+        assert_eq!(
+            vec![
+                ("div".to_string(), Namespace::Html),
+                ("svg".to_string(), Namespace::Svg),
+                ("linearGradient".to_string(), Namespace::Svg),
+                ("foreignObject".to_string(), Namespace::Svg),
+                ("p".to_string(), Namespace::Html),
+                ("math".to_string(), Namespace::MathMl),
+                ("mi".to_string(), Namespace::MathMl),
+                ("b".to_string(), Namespace::Html),
+            ],
+            namespaces
+        );
+    }
+
+    #[test]
+    fn should_recover_from_missing_and_stray_end_tags() {
+        let html = "<div><p><span>a</p></span><p>b</div></b><div>c</div>";
+        let mut tag_path = TagPath::new();
+
+        let expected_paths = [
+            "div:nth-child(1)".to_string(),
+            "div:nth-child(1) p:nth-child(1)".to_string(),
+            "div:nth-child(1) p:nth-child(1) span:nth-child(1)".to_string(),
+            "div:nth-child(1) p:nth-child(2)".to_string(),
+            "div:nth-child(2)".to_string(),
+        ];
+        let expected_recoveries = vec![
+            EndTagRecovery::ClosedOpenElements(vec!["span".to_string()]),
+            EndTagRecovery::Ignored,
+            EndTagRecovery::ClosedOpenElements(vec!["p".to_string()]),
+            EndTagRecovery::Ignored,
+            EndTagRecovery::Matched,
+        ];
+
+        let mut start_element_index = 0;
+        let mut recoveries = vec![];
+        for token in TagIterator::new(html).tokens() {
+            match token {
+                Token::Start(tag, _begin, _end) => {
+                    tag_path.add(tag);
+                    assert_tag_path(&tag_path, &expected_paths[start_element_index]);
+                    start_element_index += 1;
+                }
+                Token::End(name, _begin, _end) => recoveries.push(tag_path.close(&name)),
+                _ => {}
+            }
+        }
+        assert_eq!(expected_paths.len(), start_element_index);
+        assert_eq!(expected_recoveries, recoveries);
+        assert!(tag_path.path.items().is_empty());
+    }
+
+    #[test]
+    fn should_open_and_close_the_elements_like_an_html_parser() {
+        let mut open_elements: OpenElements = OpenElements::new();
+        for name in ["HTML", "body", "ul", "li", "p", "SVG", "linearGradient"] {
+            let depth = open_elements.depth() - open_elements.count_implied_end_tags(name);
+            open_elements.truncate(depth);
+            let namespace = open_elements.namespace_of(name);
+            open_elements.push(name, namespace, ());
+        }
+        assert_eq!(
+            "html body ul li p svg linearGradient",
+            open_elements.names_path()
+        );
+        assert!(!open_elements.folds_end_tag_case("linearGradient"));
+        assert!(open_elements.is_ignored_end_tag("br"));
+
+        assert_eq!(4, open_elements.count_implied_end_tags("li"));
+        let closed_names = ["linearGradient", "svg", "p"];
+        assert_eq!(
+            EndTagRecovery::ClosedOpenElements(
+                closed_names.iter().map(|n| n.to_string()).collect()
+            ),
+            open_elements.match_end_tag("li")
+        );
+        open_elements.truncate(5);
+        assert!(open_elements.folds_end_tag_case("P"));
+        assert_eq!(
+            EndTagRecovery::Ignored,
+            open_elements.match_end_tag("table")
+        );
+    }
+
     fn assert_tag_path(tag_path: &TagPath, expected_path: &String) {
         let names: Vec<String> = tag_path
             .path
+            .items()
             .iter()
-            .map(|boxed_tag_path_item| boxed_tag_path_item)
             .map(|tpi| {
                 let name = tpi.tag.as_ref().name.as_str();
                 let nth_child = tpi.nth_child;
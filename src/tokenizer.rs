@@ -0,0 +1,49 @@
+//! Tokenizer reading an HTML document element by element, to build other tools than the
+//! selectors of this crate.
+//!
+//! `TagIterator::new(html)` iterates over the `Elements` of `html` : the tags, the texts, the
+//! comments... The elements are read with the `ParserOptions` given with `options`, like the
+//! selectors do : the HTML code that can not be read as an element is read like a browser does,
+//! unless the options are `strict`.
+//!
+//! - `tokens` iterates over the borrowed `Token`s instead : reading a token does not allocate.
+//! - `with_depth` gives the depth of each element : the count of the elements open around it.
+//!
+//! Each element and each token has a span : the positions in bytes of its beginning and of its
+//! end, mapped to lines and columns by `LineIndex`. UTF-8 encoded bytes, even invalid, are first
+//! decoded with `decode_utf8`.
+//!
+//! New kinds of elements and of tokens may be added : a `match` on them needs a `_` arm.
+//!
+//! ```
+//! use stream_html_selector::tokenizer::{Elements, TagIterator, Token};
+//! use stream_html_selector::ParserOptions;
+//!
+//! let html = "<ul>\n  <li>caf&eacute;<br></li>\n</ul>";
+//!
+//! let first_token = TagIterator::new(html).tokens().next();
+//! assert!(matches!(first_token, Some(Token::Start(tag, 0, 4)) if tag.name == "ul"));
+//!
+//! let options = ParserOptions::new()
+//!     .decode_character_references(true)
+//!     .skip_whitespace_texts(true);
+//! let elements: Vec<String> = TagIterator::new(html)
+//!     .options(options)
+//!     .with_depth()
+//!     .map(|(element, depth)| match element {
+//!         Elements::Start(tag, _, _) => format!("{} <{}>", depth, tag.name),
+//!         Elements::End(name, _, _) => format!("{} </{}>", depth, name),
+//!         Elements::Text(text, begin, end) => format!("{} {} {}..{}", depth, text, begin, end),
+//!         _ => format!("{} other", depth),
+//!     })
+//!     .collect();
+//! assert_eq!(
+//!     vec!["0 <ul>", "1 <li>", "2 café 11..22", "2 <br>", "1 </li>", "0 </ul>"],
+//!     elements
+//! );
+//! ```
+
+pub use crate::elements::attributes::{Attribute, AttributeQuote, Attributes};
+pub use crate::elements::start_element::{Tag, TagRef};
+pub use crate::elements::tag_parser::AttributeIterator;
+pub use crate::tag_iterator::{Elements, TagIterator, Token, WithDepth};
@@ -0,0 +1,161 @@
+//! Decoding of UTF-8 encoded HTML code that may contain invalid UTF-8, like in many HTML emails.
+
+use std::borrow::Cow;
+use std::io;
+
+use crate::encoding::windows_1252_char;
+
+/// How the bytes that are not valid UTF-8 are read
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum InvalidUtf8 {
+    /// Fail with an `io::ErrorKind::InvalidData` error
+    #[default]
+    Fail,
+    /// Replace each invalid sequence with the replacement character `U+FFFD`
+    Replace,
+    /// Read each invalid byte as a Windows-1252 character, like `0xE9` as `é`. No byte is lost :
+    /// encoding these characters back in Windows-1252 gives the original bytes.
+    Windows1252,
+}
+
+/// Decode UTF-8 encoded HTML code. It only copies the HTML code if it contains invalid UTF-8.
+pub fn decode_utf8(bytes: &[u8], invalid_utf8: InvalidUtf8) -> io::Result<Cow<'_, str>> {
+    if let Ok(html) = std::str::from_utf8(bytes) {
+        return Ok(Cow::Borrowed(html));
+    }
+    let mut decoder = Utf8Decoder::new(invalid_utf8);
+    let mut html = String::with_capacity(bytes.len());
+    decoder.decode(bytes, &mut html)?;
+    decoder.finish(&mut html)?;
+    Ok(Cow::Owned(html))
+}
+
+/// Decoder of UTF-8 encoded HTML code received chunk by chunk : a character split between 2
+/// chunks is kept until the next chunk is received.
+#[derive(Debug, Default)]
+pub(crate) struct Utf8Decoder {
+    invalid_utf8: InvalidUtf8,
+    /// bytes of an UTF-8 character split between 2 chunks
+    incomplete_char: Vec<u8>,
+}
+
+impl Utf8Decoder {
+    pub fn new(invalid_utf8: InvalidUtf8) -> Self {
+        Utf8Decoder {
+            invalid_utf8,
+            incomplete_char: Vec::new(),
+        }
+    }
+
+    /// Returns true if the last chunk ended in the middle of an UTF-8 character
+    pub fn has_incomplete_char(&self) -> bool {
+        !self.incomplete_char.is_empty()
+    }
+
+    /// Decode a chunk of bytes at the end of `html`
+    pub fn decode(&mut self, bytes: &[u8], html: &mut String) -> io::Result<()> {
+        let mut bytes = bytes;
+        let mut joined_bytes;
+        if self.has_incomplete_char() {
+            joined_bytes = std::mem::take(&mut self.incomplete_char);
+            joined_bytes.extend_from_slice(bytes);
+            bytes = &joined_bytes;
+        }
+        loop {
+            match std::str::from_utf8(bytes) {
+                Ok(valid) => {
+                    html.push_str(valid);
+                    return Ok(());
+                }
+                Err(error) => {
+                    let (valid, invalid) = bytes.split_at(error.valid_up_to());
+                    html.push_str(std::str::from_utf8(valid).unwrap_or_default());
+                    match error.error_len() {
+                        None => {
+                            self.incomplete_char = invalid.to_vec();
+                            return Ok(());
+                        }
+                        Some(length) => {
+                            self.push_invalid_bytes(&invalid[..length], html)?;
+                            bytes = &invalid[length..];
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Decode the end of the bytes : a character that is not complete is invalid
+    pub fn finish(&mut self, html: &mut String) -> io::Result<()> {
+        let incomplete_char = std::mem::take(&mut self.incomplete_char);
+        if incomplete_char.is_empty() {
+            Ok(())
+        } else {
+            self.push_invalid_bytes(&incomplete_char, html)
+        }
+    }
+
+    fn push_invalid_bytes(&self, invalid_bytes: &[u8], html: &mut String) -> io::Result<()> {
+        match self.invalid_utf8 {
+            InvalidUtf8::Fail => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("invalid UTF-8 bytes {:x?}", invalid_bytes),
+                ))
+            }
+            InvalidUtf8::Replace => html.push(char::REPLACEMENT_CHARACTER),
+            InvalidUtf8::Windows1252 => {
+                html.extend(invalid_bytes.iter().map(|byte| windows_1252_char(*byte)))
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test_utf8_decoder {
+    use super::*;
+
+    #[test]
+    fn should_not_copy_valid_utf8() {
+        let html = decode_utf8("<p>été</p>".as_bytes(), InvalidUtf8::Fail).unwrap();
+        assert!(matches!(html, Cow::Borrowed("<p>été</p>")));
+    }
+
+    #[test]
+    fn should_read_invalid_utf8_with_the_given_policy() {
+        let bytes = b"<p>\xe9t\xe9 \x80 \xe2\x82</p>";
+
+        let error = decode_utf8(bytes, InvalidUtf8::Fail).unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidData, error.kind());
+        assert_eq!(
+            "<p>\u{FFFD}t\u{FFFD} \u{FFFD} \u{FFFD}</p>",
+            decode_utf8(bytes, InvalidUtf8::Replace).unwrap()
+        );
+        assert_eq!(
+            "<p>été € \u{e2}\u{201A}</p>",
+            decode_utf8(bytes, InvalidUtf8::Windows1252).unwrap()
+        );
+    }
+
+    #[test]
+    fn should_read_the_same_html_chunk_by_chunk() {
+        let bytes = "<p>€uro</p>"
+            .as_bytes()
+            .iter()
+            .chain(b"\xe2\x82")
+            .copied()
+            .collect::<Vec<u8>>();
+        let expected_html = decode_utf8(&bytes, InvalidUtf8::Windows1252).unwrap();
+
+        for chunk_size in 1..bytes.len() {
+            let mut decoder = Utf8Decoder::new(InvalidUtf8::Windows1252);
+            let mut html = String::new();
+            for chunk in bytes.chunks(chunk_size) {
+                decoder.decode(chunk, &mut html).unwrap();
+            }
+            decoder.finish(&mut html).unwrap();
+            assert_eq!(expected_html, html, "chunk size {}", chunk_size);
+        }
+    }
+}
@@ -1,6 +1,9 @@
 extern crate stream_html_selector;
 
 use stream_html_selector::css_selector;
+use stream_html_selector::{decode_html, Encoding, InvalidUtf8, ParseErrorKind};
+use stream_html_selector::ParserOptions;
+use stream_html_selector::selectors::tag_path_html_selector::TagPathHtmlSelector;
 
 fn get_command() -> String {
     let path = "./amazon_command.html";
@@ -77,3 +80,160 @@ fn should_get_all_details_of_single_command() {
 
     
 }
+
+#[test]
+fn should_get_details_of_single_command_read_from_a_file() {
+    // #header > tbody > tr:nth-child(2) > td > a
+    let command_number_matcher = vec![
+        css_selector!(#header),
+        css_selector!(tbody),
+        css_selector!(tr:nth-child(2)),
+        css_selector!(td),
+        css_selector!(a),
+    ];
+
+    let total_matcher = vec![
+        css_selector!(#costBreakdown),
+        css_selector!(tbody),
+        css_selector!(tr:nth-child(9)),
+        css_selector!(td:nth-child(2)),
+        css_selector!(strong),
+    ];
+
+    let matchers = vec![&command_number_matcher, &total_matcher];
+
+    let file = std::fs::File::open("./amazon_command.html").unwrap();
+
+    let founds =
+        stream_html_selector::by_tag_path::find_first_from_reader(file, &matchers).unwrap();
+
+    let expected = vec![
+        String::from("405-5855855-9921124"), // command number
+        String::from("EUR 61,90"),           // total amount
+    ];
+
+    assert_eq!(expected, founds);
+}
+
+#[test]
+fn should_get_details_of_single_command_with_character_references_decoded() {
+    // #orderDetails > tbody > tr > td > span
+    let command_date = vec![
+        css_selector!(#orderDetails),
+        css_selector!(tbody),
+        css_selector!(tr),
+        css_selector!(td),
+        css_selector!(span),
+    ];
+
+    // #itemDetails > tbody > tr > td.name > a:nth-child(1)
+    let label_item_1 = vec![
+        css_selector!(#itemDetails),
+        css_selector!(tbody),
+        css_selector!(tr),
+        css_selector!(td.name),
+        css_selector!(a:nth-child(1)),
+    ];
+
+    let matchers = vec![&command_date, &label_item_1];
+
+    let html = get_command();
+
+    let options = ParserOptions::new().decode_character_references(true);
+    let mut html_selector = TagPathHtmlSelector::default().options(options);
+    let founds = html_selector.find_first(html.as_str(), &matchers);
+
+    let expected = vec![
+        String::from("Effectuée le 8 juin 2019"), // command date
+        String::from(" 2\u{a0}x Rehausseur Siège auto EOS ROUGE 15-36\u{a0}kg ECE R44/04 "), // label item 1
+    ];
+
+    assert_eq!(expected, founds);
+}
+
+#[test]
+fn should_report_the_position_of_an_unterminated_comment() {
+    let total_matcher = vec![
+        css_selector!(#costBreakdown),
+        css_selector!(tbody),
+        css_selector!(tr:nth-child(9)),
+        css_selector!(td:nth-child(2)),
+        css_selector!(strong),
+    ];
+
+    let matchers = vec![&total_matcher];
+
+    let html = get_command();
+    let founds = stream_html_selector::by_tag_path::try_find_first(&html, &matchers).unwrap();
+    assert_eq!(vec![String::from("EUR 61,90")], founds);
+
+    let truncated_html = format!("{}\n<!-- unterminated", html);
+    let error =
+        stream_html_selector::by_tag_path::try_find_first(&truncated_html, &matchers).unwrap_err();
+
+    assert_eq!(ParseErrorKind::UnterminatedComment, error.kind);
+    assert_eq!(html.len() + 1, error.offset);
+    assert_eq!(truncated_html.matches('\n').count() + 1, error.line);
+    assert_eq!(1, error.column);
+}
+
+#[test]
+fn should_get_details_of_a_command_encoded_in_latin1() {
+    // #orderDetails > tbody > tr > td > span
+    let command_date = vec![
+        css_selector!(#orderDetails),
+        css_selector!(tbody),
+        css_selector!(tr),
+        css_selector!(td),
+        css_selector!(span),
+    ];
+
+    let matchers = vec![&command_date];
+
+    // `é` encoded in Latin-1 is not valid UTF-8
+    let html = get_command().replace("&eacute;", "\u{0}");
+    let html: Vec<u8> = html.bytes().map(|b| if b == 0 { 0xe9 } else { b }).collect();
+
+    let error =
+        stream_html_selector::by_tag_path::find_first_bytes(&html, &matchers, InvalidUtf8::Fail)
+            .unwrap_err();
+    assert_eq!(std::io::ErrorKind::InvalidData, error.kind());
+
+    let founds =
+        stream_html_selector::by_tag_path::find_first_bytes(&html, &matchers, InvalidUtf8::Replace)
+            .unwrap();
+    assert_eq!(vec![String::from("Effectu\u{FFFD}e le 8 juin 2019")], founds);
+
+    let founds = stream_html_selector::by_tag_path::find_first_bytes(
+        &html,
+        &matchers,
+        InvalidUtf8::Windows1252,
+    )
+    .unwrap();
+    assert_eq!(vec![String::from("Effectuée le 8 juin 2019")], founds);
+}
+
+#[test]
+fn should_get_details_of_a_command_declared_in_iso_8859_1() {
+    // #orderDetails > tbody > tr > td > span
+    let command_date = vec![
+        css_selector!(#orderDetails),
+        css_selector!(tbody),
+        css_selector!(tr),
+        css_selector!(td),
+        css_selector!(span),
+    ];
+
+    let matchers = vec![&command_date];
+
+    let html = get_command()
+        .replace("charset=UTF-8", "charset=ISO-8859-1")
+        .replace("&eacute;", "\u{0}");
+    let html: Vec<u8> = html.bytes().map(|b| if b == 0 { 0xe9 } else { b }).collect();
+
+    let decoded = decode_html(&html);
+    let founds = stream_html_selector::by_tag_path::find_first(&decoded.html, &matchers);
+
+    assert_eq!(Encoding::Windows1252, decoded.encoding);
+    assert_eq!(vec![String::from("Effectuée le 8 juin 2019")], founds);
+}
@@ -0,0 +1,30 @@
+#![cfg(feature = "async")]
+extern crate stream_html_selector;
+
+use futures_util::io::Cursor;
+use stream_html_selector::by_tag_path::{count_from_async_reader, find_first_from_async_reader};
+use stream_html_selector::css_selector;
+
+#[test]
+fn should_get_command_number_from_an_async_reader() {
+    let html = std::fs::read("./amazon_command.html").unwrap();
+
+    // #header > tbody > tr:nth-child(2) > td > a
+    let command_number_matcher = vec![
+        css_selector!(#header),
+        css_selector!(tbody),
+        css_selector!(tr:nth-child(2)),
+        css_selector!(td),
+        css_selector!(a),
+    ];
+    let matchers = vec![&command_number_matcher];
+
+    let counts =
+        futures_executor::block_on(count_from_async_reader(Cursor::new(&html), &matchers)).unwrap();
+    let founds =
+        futures_executor::block_on(find_first_from_async_reader(Cursor::new(&html), &matchers))
+            .unwrap();
+
+    assert_eq!(vec![1], counts);
+    assert_eq!(vec!["405-5855855-9921124".to_string()], founds);
+}